@@ -0,0 +1,143 @@
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+
+use std::collections::HashSet;
+
+use net_coords::network::{Network};
+use net_coords::landmarks::coords::{build_coords, choose_landmarks};
+use net_coords::landmarks::bourgain::{build_bourgain_coords, bourgain_dims};
+use net_coords::landmarks::{find_path_landmarks_areas_approx, gen_areas, KnownNode};
+use net_coords::landmarks::coord_mappers::{approx_max_dist};
+use net_coords::network_gen::{gen_network};
+use net_coords::random_util::choose_k_nums;
+
+
+/*
+ * Compare Bourgain coordinates with single landmark coordinates:
+ * How well they approximate network distances, and how well they route.
+ */
+
+/// Print distortion and routing results of a coordinates system over random node pairs.
+fn print_embedding_stats(node_pairs: &[(usize, usize)], net: &Network<u64>,
+                         coords: &Vec<Vec<u64>>, landmarks: &Vec<usize>,
+                         areas: &Vec<Vec<KnownNode>>, route_rng: &mut StdRng) {
+
+    let mut sum_distortion: f64 = 0.0;
+    let mut max_distortion: f64 = 0.0;
+    let mut num_paths_found: usize = 0;
+    let mut sum_path_len: u64 = 0;
+
+    for &(src_node, dst_node) in node_pairs {
+        let dist = net.dist(src_node, dst_node).unwrap();
+        let approx_dist = approx_max_dist(src_node, dst_node, coords, landmarks);
+        // approx_max_dist never overestimates the distance:
+        let distortion = (dist as f64) / (approx_dist.max(1) as f64);
+        sum_distortion += distortion;
+        if distortion > max_distortion {
+            max_distortion = distortion;
+        }
+
+        let mut hs = HashSet::new();
+        hs.insert(dst_node);
+        let opt_path_len = find_path_landmarks_areas_approx(src_node, &hs, &coords[dst_node],
+                                   net.igraph.node_count() as u64, net,
+                                   coords, landmarks, areas, route_rng);
+        if let Some(path_len) = opt_path_len {
+            sum_path_len += path_len;
+            num_paths_found += 1;
+        }
+    }
+
+    let num_pairs = node_pairs.len() as f64;
+    print!(" dims = {:5},", coords[0].len());
+    print!(" distortion = {:7.3},", sum_distortion / num_pairs);
+    print!(" max_distortion = {:8.3},", max_distortion);
+    print!(" found_ratio = {:5.3},", (num_paths_found as f64) / num_pairs);
+    print!(" avg_path_len = {:8.3}", (sum_path_len as f64) / (num_paths_found as f64));
+}
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_pairs = 100;
+    let bourgain_seed = 0;
+
+    println!("Compare Bourgain coordinates with landmarks coordinates");
+    println!();
+    println!("num_pairs = {}", num_pairs);
+    println!();
+
+    for g in 6 .. 12 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                print!("g={:2}; ",g);
+                match net_type {
+                    0 => print!("rand    ; "),
+                    1 => print!("2d      ; "),
+                    2 => print!("rand+2d ; "),
+                    3 => print!("planar  ; "),
+                    4 => print!("tree    ; "),
+                    _ => unreachable!(),
+                }
+                print!("ni={:1} |",net_iter);
+
+                /* Generate network */
+                let seed: &[_] = &[1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 0x10000, 0x20000, &mut network_rng);
+                let num_nodes = net.igraph.node_count();
+
+                let amount_close = ((num_nodes as f64).log(2.0) as usize).pow(2);
+                // Amount of landmarks can not be above half of the node count:
+                let mut num_landmarks: usize = g * g;
+                if num_landmarks as f64 > (num_nodes as f64) / 2.0 {
+                    num_landmarks = num_nodes / 2;
+                }
+                let areas = gen_areas(amount_close, &net);
+                let landmarks = choose_landmarks(&net, num_landmarks, &mut network_rng);
+                let coords = match build_coords(&net, &landmarks) {
+                    Some(coords) => coords,
+                    None => unreachable!(),
+                };
+
+                let (m, t) = bourgain_dims(num_nodes);
+                let bcoords = match build_bourgain_coords(&net, m, t, bourgain_seed) {
+                    Some(bcoords) => bcoords,
+                    None => unreachable!(),
+                };
+
+                let mut pair_rng: StdRng =
+                    rand::SeedableRng::from_seed(&[2,g, net_type, net_iter] as &[_]);
+                let node_pairs = (0 .. num_pairs)
+                    .map(|_| {
+                        let mut node_pair = choose_k_nums(2, num_nodes, &mut pair_rng)
+                            .into_iter().collect::<Vec<usize>>();
+                        // Sort for determinism:
+                        node_pair.sort();
+                        (node_pair[0], node_pair[1])
+                    })
+                    .collect::<Vec<(usize, usize)>>();
+
+                print!(" landmarks:");
+                let mut route_rng: StdRng =
+                    rand::SeedableRng::from_seed(&[4,g, net_type, net_iter] as &[_]);
+                print_embedding_stats(&node_pairs, &net, &coords, &landmarks,
+                                      &areas, &mut route_rng);
+
+                // Bourgain coordinates have no landmarks:
+                print!(" | bourgain:");
+                let mut route_rng: StdRng =
+                    rand::SeedableRng::from_seed(&[4,g, net_type, net_iter] as &[_]);
+                print_embedding_stats(&node_pairs, &net, &bcoords.coords, &Vec::new(),
+                                      &areas, &mut route_rng);
+                println!();
+            }
+        }
+        println!();
+    }
+}
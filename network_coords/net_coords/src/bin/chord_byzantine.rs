@@ -0,0 +1,89 @@
+/*
+ * Measure the effect of byzantine nodes on chord fingers convergence.
+ * For every fraction of byzantine nodes and every byzantine behavior,
+ * count poisoned honest fingers and failed lookups.
+ */
+
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+
+use net_coords::network_gen::{gen_network};
+use net_coords::chord::{init_fingers};
+use net_coords::chord::byzantine::{Adversary, ByzantineBehavior,
+    converge_fingers_byzantine, byzantine_report};
+
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_lookups = 1000;
+    let experiment_seed = 0x1337;
+    let fractions = [0.0, 0.01, 0.05, 0.1, 0.2];
+
+    println!("Byzantine nodes in chord fingers convergence");
+    println!();
+
+    for g in 6 .. 13 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        // Eclipse the first quarter of the ring:
+        let behaviors = vec![
+            ("fake_short", ByzantineBehavior::FakeShortChains),
+            ("drop", ByzantineBehavior::DropUpdates),
+            ("eclipse", ByzantineBehavior::Eclipse(0, 2_u64.pow(l as u32 - 2))),
+        ];
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+
+                /* Generate network */
+                let seed: &[_] = &[experiment_seed,1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 1, 2, &mut network_rng);
+
+                for &(behavior_name, ref behavior) in &behaviors {
+                    for (fi, &fraction) in fractions.iter().enumerate() {
+                        print!("g={:2}; ",g);
+                        match net_type {
+                            0 => print!("rand    ; "),
+                            1 => print!("2d      ; "),
+                            2 => print!("rand+2d ; "),
+                            3 => print!("planar  ; "),
+                            4 => print!("tree    ; "),
+                            _ => unreachable!(),
+                        }
+                        print!("ni={:1} |",net_iter);
+                        print!("{:10}| byz={:4.2} |", behavior_name, fraction);
+
+                        let seed: &[_] = &[experiment_seed,2,g,net_type,net_iter,fi];
+                        let mut adversary_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                        let mut adversary = Adversary::new(&net, fraction,
+                                                           behavior.clone(), &mut adversary_rng);
+
+                        let seed: &[_] = &[experiment_seed,3,g,net_type,net_iter];
+                        let mut fingers_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                        let mut fingers = init_fingers(&net, l, &mut fingers_rng);
+                        converge_fingers_byzantine(&net, &mut fingers, l, &mut adversary);
+
+                        let seed: &[_] = &[experiment_seed,4,g,net_type,net_iter];
+                        let mut lookup_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                        let report = byzantine_report(&net, &fingers, &adversary,
+                                                      num_lookups, &mut lookup_rng);
+
+                        let honest_fingers = report.honest_fingers as f64;
+                        print!(" poisoned = {:5.3} |",
+                               (report.poisoned_fingers as f64) / honest_fingers);
+                        print!(" suboptimal = {:5.3} |",
+                               (report.suboptimal_fingers as f64) / honest_fingers);
+                        print!(" failed_lookups = {:5.3}",
+                               (report.failed_lookups as f64) / (report.lookups as f64));
+                        println!();
+                    }
+                }
+            }
+        }
+        println!();
+    }
+}
@@ -0,0 +1,102 @@
+/*
+ * Compare various finger strategies for chord.
+ * For every strategy check if the fingers are globally optimal,
+ * and find the average route length.
+ */
+
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+extern crate ordered_float;
+
+use rand::{StdRng};
+
+use net_coords::network_gen::{gen_network};
+use net_coords::chord::{init_fingers_by_strategy,
+    converge_fingers, create_semi_chains, find_path,
+    verify_global_optimality};
+use net_coords::chord::finger_strategy::{FingerStrategy};
+use net_coords::random_util::choose_k_nums;
+
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let pair_iters = 1000;
+    let experiment_seed = 0x1337;
+
+    println!("Comparing chord finger strategies");
+    println!();
+
+    for g in 6 .. 21 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+
+                /* Generate network */
+                let seed: &[_] = &[experiment_seed,1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 1, 2, &mut network_rng);
+
+                for (strategy_name, finger_strategy) in FingerStrategy::presets(l) {
+                    print!("g={:2}; ",g);
+                    match net_type {
+                        0 => print!("rand    ; "),
+                        1 => print!("2d      ; "),
+                        2 => print!("rand+2d ; "),
+                        3 => print!("planar  ; "),
+                        4 => print!("tree    ; "),
+                        _ => unreachable!(),
+                    }
+                    print!("ni={:1} |",net_iter);
+                    print!("{:16}|", strategy_name);
+
+                    let seed: &[_] = &[experiment_seed,2,g,net_type,net_iter];
+                    let mut fingers_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                    let mut fingers = init_fingers_by_strategy(&net, l,
+                                                       &finger_strategy, &mut fingers_rng);
+                    converge_fingers(&net, &mut fingers, l);
+
+                    // Average amount of maintained fingers per node:
+                    let sum_fingers: usize = fingers.iter()
+                        .map(|nf| nf.left.sorted_fingers.len() + nf.right.sorted_fingers.len())
+                        .sum();
+                    let avg_fingers = (sum_fingers as f64) / (net.igraph.node_count() as f64);
+                    print!(" avg_fingers = {:8.2} |", avg_fingers);
+
+                    print!(" optimal = ");
+                    if verify_global_optimality(&net, &fingers) {
+                        print!("V");
+                    } else {
+                        print!("X");
+                    }
+
+                    let semi_chains = create_semi_chains(&net, &fingers);
+
+                    let seed: &[_] = &[experiment_seed,3,g,net_type,net_iter];
+                    let mut pair_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                    let mut sum_length: u64 = 0;
+                    let mut num_found: usize = 0;
+                    for _ in 0 .. pair_iters {
+                        let mut node_pair: Vec<usize> =
+                            choose_k_nums(2,net.igraph.node_count(),&mut pair_rng)
+                            .into_iter().collect::<Vec<_>>();
+                        node_pair.sort(); // Make computation deterministic
+                        let src_id = *net.index_to_node(node_pair[0]).unwrap();
+                        let dst_id = *net.index_to_node(node_pair[1]).unwrap();
+
+                        if let Some(path_len) = find_path(src_id, dst_id, &net, &semi_chains) {
+                            sum_length += path_len as u64;
+                            num_found += 1;
+                        }
+                    }
+                    print!(" | avg_path_len = {:8.3}", (sum_length as f64) / (num_found as f64));
+                    print!(" | found_ratio = {:5.3}", (num_found as f64) / (pair_iters as f64));
+                    println!();
+                }
+            }
+        }
+        println!();
+    }
+}
@@ -0,0 +1,122 @@
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+
+use std::collections::HashSet;
+
+use net_coords::landmarks::coords::{build_coords, choose_landmarks};
+use net_coords::landmarks::{find_path_landmarks_areas_approx, gen_areas};
+use net_coords::landmarks::codec::{CoordCodec};
+use net_coords::network_gen::{gen_network};
+use net_coords::random_util::choose_k_nums;
+
+
+/*
+ * Encode coordinates using a compact lossy codec, and check how the quantization error
+ * affects finding a node by its decoded coordinate.
+ */
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_pairs = 100;
+    let max_errors = [0, 0x400, 0x1000, 0x4000, 0x10000];
+
+    println!("Compact coordinate encoding with quantization");
+    println!();
+    println!("num_pairs = {}", num_pairs);
+    println!("max_errors = {:x?}", max_errors);
+    println!();
+
+    for g in 8 .. 20 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                /* Generate network */
+                let seed: &[_] = &[1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 0x10000, 0x20000, &mut network_rng);
+                let num_nodes = net.igraph.node_count();
+
+                let amount_close = ((num_nodes as f64).log(2.0) as usize).pow(2);
+                // Amount of landmarks can not be above half of the node count:
+                let mut num_landmarks: usize = g * g;
+                if num_landmarks as f64 > (num_nodes as f64) / 2.0 {
+                    num_landmarks = num_nodes / 2;
+                }
+                let areas = gen_areas(amount_close, &net);
+                let landmarks = choose_landmarks(&net, num_landmarks, &mut network_rng);
+                let coords = match build_coords(&net, &landmarks) {
+                    Some(coords) => coords,
+                    None => unreachable!(),
+                };
+                let raw_size = num_landmarks * 8;
+
+                for &max_error in &max_errors {
+                    print!("g={:2}; ",g);
+                    match net_type {
+                        0 => print!("rand    ; "),
+                        1 => print!("2d      ; "),
+                        2 => print!("rand+2d ; "),
+                        3 => print!("planar  ; "),
+                        4 => print!("tree    ; "),
+                        _ => unreachable!(),
+                    }
+                    print!("ni={:1} |",net_iter);
+                    print!(" max_error = {:5x} |", max_error);
+
+                    let codec = CoordCodec::new(&landmarks, &coords, max_error);
+                    let encoded_sizes = coords.iter()
+                        .map(|coord| codec.encode(coord).len())
+                        .collect::<Vec<usize>>();
+                    let avg_size = (encoded_sizes.iter().sum::<usize>() as f64) /
+                        (num_nodes as f64);
+                    let max_size = *encoded_sizes.iter().max().unwrap();
+
+                    let mut pair_rng: StdRng =
+                        rand::SeedableRng::from_seed(&[2,g, net_type, net_iter] as &[_]);
+                    let mut route_rng: StdRng =
+                        rand::SeedableRng::from_seed(&[4,g, net_type, net_iter] as &[_]);
+
+                    let mut num_paths_found: usize = 0;
+                    let mut sum_path_len: u64 = 0;
+                    for _ in 0 .. num_pairs {
+                        // Randomize a pair of nodes.
+                        let mut node_pair = choose_k_nums(2, num_nodes, &mut pair_rng)
+                            .into_iter().collect::<Vec<usize>>();
+                        // Sort for determinism:
+                        node_pair.sort();
+
+                        // Send node_pair[1]'s coordinate over the wire:
+                        let buf = codec.encode(&coords[node_pair[1]]);
+                        let decoded_coord = codec.decode(&buf).unwrap();
+
+                        let mut hs = HashSet::new();
+                        hs.insert(node_pair[1]);
+                        let opt_path_len =
+                            find_path_landmarks_areas_approx(node_pair[0], &hs, &decoded_coord,
+                                       num_nodes as u64, &net,
+                                       &coords, &landmarks, &areas, &mut route_rng);
+                        if let Some(path_len) = opt_path_len {
+                            sum_path_len += path_len;
+                            num_paths_found += 1;
+                        }
+                    }
+
+                    print!(" raw_size = {:5} |", raw_size);
+                    print!(" avg_size = {:8.3} |", avg_size);
+                    print!(" max_size = {:5} |", max_size);
+                    print!(" found_ratio = {:5.3} |",
+                           (num_paths_found as f64) / (num_pairs as f64));
+                    print!(" avg_path_len = {:8.3}",
+                           (sum_path_len as f64) / (num_paths_found as f64));
+                    println!();
+                }
+            }
+        }
+        println!();
+    }
+}
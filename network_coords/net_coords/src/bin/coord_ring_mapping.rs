@@ -0,0 +1,133 @@
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+
+use net_coords::network::{Network};
+use net_coords::chord::{RingKey, ring_dist};
+use net_coords::landmarks::coords::{build_coords, choose_landmarks};
+use net_coords::landmarks::coord_mappers::{RingMapper, fraction_to_key, coord_to_ring,
+    coord_to_ring_all_pairs, coord_to_ring_adj_pairs, old_coord_to_ring};
+use net_coords::landmarks::distortion::{sample_node_pairs};
+use net_coords::landmarks::load_balance::{gini};
+use net_coords::statistic::{spearman};
+use net_coords::network_gen::{gen_network};
+
+
+/*
+ * Evaluate mappings of network coordinates to chord ring keys:
+ * Do nodes that are close in the network get close keys (Locality),
+ * and are the keys spread evenly around the ring (Uniformity)?
+ */
+
+/// A function that maps a coordinate to a value in [0,1).
+type CoordToRing = fn(&Vec<u64>) -> f64;
+
+/// Print locality and uniformity of the given keys of all nodes.
+fn print_keys_stats(mapper_name: &str, keys: &[RingKey], l: usize, net: &Network<u64>,
+                    node_pairs: &[(usize, usize)]) {
+
+    let avg_ring_dist = |pairs: &mut dyn Iterator<Item=(usize, usize)>| {
+        let (sum, count) = pairs.fold((0.0, 0), |(sum, count), (u, v)|
+            (sum + ring_dist(keys[u], keys[v], l) as f64, count + 1));
+        sum / (count as f64)
+    };
+    // Average ring distance of adjacent nodes, relative to random nodes:
+    let adjacent_dist = avg_ring_dist(&mut net.igraph.all_edges().map(|(u, v, _)| (u, v)));
+    let random_dist = avg_ring_dist(&mut node_pairs.iter().cloned());
+
+    let net_dists = node_pairs.iter()
+        .map(|&(u, v)| net.dist(u, v).unwrap() as f64)
+        .collect::<Vec<f64>>();
+    let ring_dists = node_pairs.iter()
+        .map(|&(u, v)| ring_dist(keys[u], keys[v], l) as f64)
+        .collect::<Vec<f64>>();
+
+    // Split the ring into one bucket per node:
+    let num_nodes = keys.len();
+    let mut bucket_hits = vec![0; num_nodes];
+    for &key in keys {
+        let bucket = ((key as f64) / 2.0_f64.powi(l as i32) * (num_nodes as f64)) as usize;
+        bucket_hits[bucket.min(num_nodes - 1)] += 1;
+    }
+
+    print!("    {:24}|", mapper_name);
+    print!(" adjacent/random = {:6.3} |", adjacent_dist / random_dist);
+    print!(" spearman = {:6.3} |", spearman(&net_dists, &ring_dists).unwrap());
+    print!(" gini = {:5.3} |", gini(&bucket_hits));
+    print!(" max_bucket = {:4}", bucket_hits.iter().max().unwrap());
+    println!();
+}
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_pairs = 500;
+
+    println!("Locality and uniformity of coordinate to ring mappings");
+    println!();
+    println!("num_pairs = {}", num_pairs);
+    println!();
+
+    let mappers: [(&str, CoordToRing); 4] = [
+        ("coord_to_ring", coord_to_ring),
+        ("coord_to_ring_all_pairs", coord_to_ring_all_pairs),
+        ("coord_to_ring_adj_pairs", coord_to_ring_adj_pairs),
+        ("old_coord_to_ring", old_coord_to_ring),
+    ];
+
+    for g in 6 .. 14 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                print!("g={:2}; ",g);
+                match net_type {
+                    0 => print!("rand    ; "),
+                    1 => print!("2d      ; "),
+                    2 => print!("rand+2d ; "),
+                    3 => print!("planar  ; "),
+                    4 => print!("tree    ; "),
+                    _ => unreachable!(),
+                }
+                println!("ni={:1}",net_iter);
+
+                /* Generate network */
+                let seed: &[_] = &[1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 0x10000, 0x20000, &mut network_rng);
+                let num_nodes = net.igraph.node_count();
+
+                // Amount of landmarks can not be above half of the node count:
+                let mut num_landmarks: usize = g * g;
+                if num_landmarks as f64 > (num_nodes as f64) / 2.0 {
+                    num_landmarks = num_nodes / 2;
+                }
+                let landmarks = choose_landmarks(&net, num_landmarks, &mut network_rng);
+                let coords = match build_coords(&net, &landmarks) {
+                    Some(coords) => coords,
+                    None => unreachable!(),
+                };
+
+                let mut pair_rng: StdRng =
+                    rand::SeedableRng::from_seed(&[2,g, net_type, net_iter] as &[_]);
+                let node_pairs = sample_node_pairs(num_nodes, num_pairs, &mut pair_rng);
+
+                let ring_mapper = RingMapper::new(&landmarks, &coords, l);
+                let keys = coords.iter()
+                    .map(|coord| ring_mapper.coord_to_key(coord))
+                    .collect::<Vec<RingKey>>();
+                print_keys_stats("ring_mapper", &keys, l, &net, &node_pairs);
+
+                for &(mapper_name, mapper) in &mappers {
+                    let keys = coords.iter()
+                        .map(|coord| fraction_to_key(mapper(coord), l))
+                        .collect::<Vec<RingKey>>();
+                    print_keys_stats(mapper_name, &keys, l, &net, &node_pairs);
+                }
+            }
+        }
+        println!();
+    }
+}
@@ -0,0 +1,112 @@
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+use rand::distributions::{IndependentSample, Range};
+
+use net_coords::landmarks::coords::{build_coords, choose_landmarks};
+use net_coords::landmarks::{gen_areas};
+use net_coords::landmarks::storage::{CoordStorage, RetryPolicy, LookupStats};
+use net_coords::network_gen::{gen_network};
+
+
+/*
+ * Store keys at coordinates derived from the keys, and look them up
+ * from random nodes in the network. Check the effect of replication.
+ */
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_keys = 100;
+    let lookups_per_key = 4;
+    let replications = [1, 2, 4, 8];
+
+    println!("Coordinate addressed storage: store keys, and look them up from random nodes.");
+    println!();
+    println!("num_keys = {}", num_keys);
+    println!("lookups_per_key = {}", lookups_per_key);
+    println!();
+
+    for g in 8 .. 20 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                /* Generate network */
+                let seed: &[_] = &[1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 0x10000, 0x20000 , &mut network_rng);
+
+                let amount_close = ((net.igraph.node_count() as f64).log(2.0) as usize).pow(2);
+
+                // Amount of landmarks can not be above half of the node count:
+                let mut num_landmarks: usize = g * g;
+                if num_landmarks as f64 > (net.igraph.node_count() as f64) / 2.0 {
+                    num_landmarks = net.igraph.node_count() / 2;
+                }
+                let areas = gen_areas(amount_close, &net);
+                let landmarks = choose_landmarks(&net, num_landmarks, &mut network_rng);
+                let coords = match build_coords(&net, &landmarks) {
+                    Some(coords) => coords,
+                    None => unreachable!(),
+                };
+
+                for &num_replicas in &replications {
+                    print!("g={:2}; ",g);
+                    match net_type {
+                        0 => print!("rand    ; "),
+                        1 => print!("2d      ; "),
+                        2 => print!("rand+2d ; "),
+                        3 => print!("planar  ; "),
+                        4 => print!("tree    ; "),
+                        _ => unreachable!(),
+                    }
+                    print!("ni={:1} |",net_iter);
+                    print!(" replicas = {:2} |", num_replicas);
+
+                    let retry_policy = RetryPolicy {
+                        max_visits: 2,
+                        max_path_len: net.igraph.node_count() as u64,
+                        max_attempts: g,
+                    };
+                    let mut storage = CoordStorage::new(&net, &coords, &landmarks, &areas,
+                                                        num_replicas, retry_policy);
+
+                    let mut node_rng: StdRng =
+                        rand::SeedableRng::from_seed(&[2,g, net_type, net_iter] as &[_]);
+                    let mut route_rng: StdRng =
+                        rand::SeedableRng::from_seed(&[4,g, net_type, net_iter] as &[_]);
+                    let node_range: Range<usize> = Range::new(0, net.igraph.node_count());
+
+                    let mut lookup_stats = LookupStats::default();
+                    let mut sum_store_path_len: u64 = 0;
+                    for key_num in 0 .. num_keys {
+                        let key = format!("key{}", key_num).into_bytes();
+                        let store_result = storage.store(node_range.ind_sample(&mut node_rng),
+                                                         &key, &mut route_rng);
+                        sum_store_path_len += store_result.path_len;
+
+                        for _ in 0 .. lookups_per_key {
+                            let lookup_result = storage.lookup(node_range.ind_sample(&mut node_rng),
+                                                               &key, &mut route_rng);
+                            lookup_stats.add(&lookup_result);
+                        }
+                    }
+
+                    let max_load = storage.node_load().into_iter().max().unwrap();
+
+                    print!(" store_path_len = {:8.3} |",
+                           (sum_store_path_len as f64) / (num_keys as f64));
+                    print!(" found = {:5.3} |", lookup_stats.success_ratio());
+                    print!(" lookup_path_len = {:8.3} |", lookup_stats.avg_path_len());
+                    print!(" attempts = {:6.3} |", lookup_stats.avg_attempts());
+                    print!(" max_load = {:4}", max_load);
+                    println!();
+                }
+            }
+        }
+        println!();
+    }
+}
@@ -0,0 +1,134 @@
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+
+use net_coords::network::{Network};
+use net_coords::landmarks::coords::{build_coords, choose_landmarks};
+use net_coords::landmarks::coord_mappers::{CoordMetric, CoordsMetric, approx_max_dist,
+    approx_avg_dist, approx_pairs_dist1, approx_pairs_dist2, approx_pairs_dist1_normalized,
+    approx_pairs_dist2_normalized, max_dist};
+use net_coords::landmarks::bourgain::{build_bourgain_coords, bourgain_dims};
+use net_coords::landmarks::distortion::{distortion_report, sample_node_pairs,
+    REL_ERROR_PERCENTILES};
+use net_coords::network_gen::{gen_network};
+use net_coords::vivaldi::{init_vivaldi, choose_peers, converge_vivaldi, VivaldiParams};
+
+
+/*
+ * Measure how well every coordinate metric predicts real network distances.
+ */
+
+/// A function that approximates distance using landmarks coordinates.
+type Mapper = fn(usize, usize, &Vec<Vec<u64>>, &Vec<usize>) -> f64;
+
+fn print_report<M: CoordMetric + ?Sized>(metric_name: &str, net: &Network<u64>, metric: &M,
+                                         node_pairs: &[(usize, usize)]) {
+    let report = distortion_report(net, metric, node_pairs);
+    print!("    {:24}|", metric_name);
+    print!(" pearson = {:6.3} |", report.pearson);
+    print!(" spearman = {:6.3} |", report.spearman);
+    print!(" rel_err");
+    for (p, e) in REL_ERROR_PERCENTILES.iter().zip(&report.rel_error_percentiles) {
+        print!(" p{}={:12.3}", p, e);
+    }
+    if let (Some(lower), Some(upper)) = (report.lower_tight_ratio, report.upper_tight_ratio) {
+        print!(" | tight: lower = {:5.3}, upper = {:5.3}", lower, upper);
+    }
+    println!();
+}
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_pairs = 200;
+    let params = VivaldiParams {
+        dims: 3,
+        cc: 0.25,
+        ce: 0.25,
+    };
+    let vivaldi_rounds = 200;
+    let num_remote_peers = 8;
+
+    println!("Distortion of coordinate metrics");
+    println!();
+    println!("num_pairs = {}", num_pairs);
+    println!();
+
+    let mappers: [(&str, Mapper); 6] = [
+        ("approx_max_dist", |u, v, coords, landmarks|
+            approx_max_dist(u, v, coords, landmarks) as f64),
+        ("approx_avg_dist", approx_avg_dist),
+        ("approx_pairs_dist1", approx_pairs_dist1),
+        ("approx_pairs_dist2", approx_pairs_dist2),
+        ("approx_pairs_dist1_norm", approx_pairs_dist1_normalized),
+        ("approx_pairs_dist2_norm", approx_pairs_dist2_normalized),
+    ];
+
+    for g in 6 .. 11 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                print!("g={:2}; ",g);
+                match net_type {
+                    0 => print!("rand    ; "),
+                    1 => print!("2d      ; "),
+                    2 => print!("rand+2d ; "),
+                    3 => print!("planar  ; "),
+                    4 => print!("tree    ; "),
+                    _ => unreachable!(),
+                }
+                println!("ni={:1}",net_iter);
+
+                /* Generate network */
+                let seed: &[_] = &[1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 0x10000, 0x20000, &mut network_rng);
+                let num_nodes = net.igraph.node_count();
+
+                // Amount of landmarks can not be above half of the node count:
+                let mut num_landmarks: usize = g * g;
+                if num_landmarks as f64 > (num_nodes as f64) / 2.0 {
+                    num_landmarks = num_nodes / 2;
+                }
+                let landmarks = choose_landmarks(&net, num_landmarks, &mut network_rng);
+                let coords = match build_coords(&net, &landmarks) {
+                    Some(coords) => coords,
+                    None => unreachable!(),
+                };
+
+                let mut pair_rng: StdRng =
+                    rand::SeedableRng::from_seed(&[2,g, net_type, net_iter] as &[_]);
+                let node_pairs = sample_node_pairs(num_nodes, num_pairs, &mut pair_rng);
+
+                for &(mapper_name, mapper) in &mappers {
+                    let metric = CoordsMetric {
+                        coords: &coords,
+                        landmarks: &landmarks,
+                        mapper,
+                    };
+                    print_report(mapper_name, &net, &metric, &node_pairs);
+                }
+
+                let (m, t) = bourgain_dims(num_nodes);
+                let bcoords = match build_bourgain_coords(&net, m, t, 0) {
+                    Some(bcoords) => bcoords,
+                    None => unreachable!(),
+                };
+                let bourgain_metric = |u: usize, v: usize|
+                    max_dist(&bcoords.coords[u], &bcoords.coords[v]) as f64;
+                print_report("bourgain_max_dist", &net, &bourgain_metric, &node_pairs);
+
+                let mut vivaldi_rng: StdRng =
+                    rand::SeedableRng::from_seed(&[3,g, net_type, net_iter] as &[_]);
+                let peers = choose_peers(&net, num_remote_peers, &mut vivaldi_rng);
+                let mut nodes = init_vivaldi(&net, params.dims);
+                converge_vivaldi(&peers, &mut nodes, &params, vivaldi_rounds, &mut vivaldi_rng);
+                print_report("vivaldi", &net, &nodes[..], &node_pairs);
+            }
+        }
+        println!();
+    }
+}
@@ -24,6 +24,8 @@ use net_coords::chord::{init_fingers,
     verify_global_optimality};
 
 use net_coords::chord::{RingKey};
+use net_coords::chord::kademlia::{init_kbuckets, converge_kbuckets, find_path_xor};
+use net_coords::towers::{choose_towers, calc_towers_info, TowersOverlay, find_path_towers};
 
 
 
@@ -86,9 +88,11 @@ struct RoutePrecompute<'a, Node: 'a> {
 
 fn run_routing_by_type<R: Rng>(routing_type: usize, 
        net: &Network<RingKey>, g: usize, l: usize,
-        mut node_pair_rng: &mut R, mut routing_rng: &mut R) -> RoutingStats {
+        node_pair_rng: &mut R, mut routing_rng: &mut R) -> RoutingStats {
 
     let chord_num_iters = 1000;
+    // Size of every k-bucket for kademlia routing:
+    let kademlia_k = 8;
     let landmarks_num_iters = 100;
     let avg_degree = ((((2*net.igraph.edge_count()) as f64) / 
         (net.igraph.node_count() as f64)) + 1.0) as usize;
@@ -118,7 +122,7 @@ fn run_routing_by_type<R: Rng>(routing_type: usize,
             };
 
             get_routing_stats(&mut rand_node_pair, &mut find_path,
-                                  &mut node_pair_rng, chord_num_iters)
+                                  node_pair_rng, chord_num_iters)
         },
         1 => { /* landmarks routing nei^2 */
             // Generate helper structures for landmarks routing:
@@ -142,7 +146,7 @@ fn run_routing_by_type<R: Rng>(routing_type: usize,
             };
 
             get_routing_stats(&mut rand_node_pair, &mut find_path,
-                                  &mut node_pair_rng, landmarks_num_iters)
+                                  node_pair_rng, landmarks_num_iters)
         },
         2 => { /* landmarks routing nei^3 */
             // Generate helper structures for landmarks routing:
@@ -166,7 +170,56 @@ fn run_routing_by_type<R: Rng>(routing_type: usize,
             };
 
             get_routing_stats(&mut rand_node_pair, &mut find_path,
-                              &mut node_pair_rng, landmarks_num_iters)
+                              node_pair_rng, landmarks_num_iters)
+        },
+        3 => { /* chord routing, both ring directions */
+            // Generate helper structures for chord routing:
+            let mut fingers = init_fingers(net,l, &mut routing_rng);
+            converge_fingers(net, &mut fingers,l);
+            assert!(verify_global_optimality(net, &fingers));
+            let semi_chains = create_semi_chains(net, &fingers);
+
+            let mut find_path = |src_i: usize, dst_i: usize| {
+                let src_id = *net.index_to_node(src_i).unwrap();
+                let dst_id = *net.index_to_node(dst_i).unwrap();
+                chord::find_path_bidir(src_id, dst_id, net, &semi_chains, l)
+                    .map(|x| x as u64)
+            };
+
+            get_routing_stats(&mut rand_node_pair, &mut find_path,
+                                  node_pair_rng, chord_num_iters)
+        },
+        4 => { /* kademlia XOR routing */
+            // Generate helper structures for XOR routing:
+            let mut kbuckets = init_kbuckets(net, kademlia_k, l);
+            converge_kbuckets(net, &mut kbuckets);
+
+            let mut find_path = |src_i: usize, dst_i: usize| {
+                let src_id = *net.index_to_node(src_i).unwrap();
+                let dst_id = *net.index_to_node(dst_i).unwrap();
+                find_path_xor(src_id, dst_id, net, &kbuckets)
+                    .map(|x| x as u64)
+            };
+
+            get_routing_stats(&mut rand_node_pair, &mut find_path,
+                                  node_pair_rng, chord_num_iters)
+        },
+        5 => { /* towers routing */
+            // Generate helper structures for towers routing:
+            let num_colors = g*2;
+            let total_num_towers = (net.igraph.node_count() as f64).sqrt() as usize;
+            let num_towers = 1 + (total_num_towers / num_colors);
+            let chosen_towers = choose_towers(net, num_towers, num_colors, &mut routing_rng);
+            let towers_info = calc_towers_info(net, &chosen_towers);
+            let overlay = TowersOverlay::new(&chosen_towers, &towers_info);
+
+            let mut find_path = |src_i: usize, dst_i: usize| {
+                find_path_towers(src_i, dst_i, &towers_info, &overlay)
+                    .map(|route| route.total_len())
+            };
+
+            get_routing_stats(&mut rand_node_pair, &mut find_path,
+                                  node_pair_rng, landmarks_num_iters)
         },
         _ => unreachable!(),
     }
@@ -175,9 +228,9 @@ fn run_routing_by_type<R: Rng>(routing_type: usize,
 
 #[cfg(not(test))]
 fn main() {
-    let net_types = 3;
+    let net_types = 4;
     let net_iters = 3;
-    let routing_types = 3;
+    let routing_types = 6;
     let experiment_seed = 0x1337;
     // Keep the last max route length for combinations of [net_type][routing_type]
     let mut last_max_route_lengths: Vec<Vec<u64>> =
@@ -188,8 +241,8 @@ fn main() {
     // and routing_type
     let allowed_max_route_length = 10000;
 
-    println!("      Network        |          chord         |    landmarks nei^2     |     landmarks nei^3     ");
-    println!("---------------------+------------------------+------------------------+------------------------+");
+    println!("      Network        |          chord         |    landmarks nei^2     |     landmarks nei^3    |      chord bidir       |      kademlia xor      |         towers         ");
+    println!("---------------------+------------------------+------------------------+------------------------+------------------------+------------------------+------------------------+");
 
     for g in 6 .. 21 { // Iterate over size of network.
         let l = 2 * g + 1;
@@ -200,6 +253,7 @@ fn main() {
                     0 => print!("rand    ; "),
                     1 => print!("2d      ; "),
                     2 => print!("rand+2d ; "),
+                    3 => print!("planar  ; "),
                     _ => unreachable!(),
                 }
                 print!("ni={:1} |",net_iter);
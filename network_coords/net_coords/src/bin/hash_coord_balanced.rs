@@ -0,0 +1,101 @@
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+use net_coords::landmarks::coords::{build_coords, choose_landmarks};
+use net_coords::landmarks::randomize_coord::{calc_upper_constraints,
+    randomize_coord_by_strategy, RandomizeStrategy};
+use net_coords::landmarks::hash_coord::{hash_to_coord};
+use net_coords::landmarks::coord_mappers::{max_dist};
+use net_coords::network_gen::{gen_network};
+
+
+/*
+ * Check if mapping keys to coordinates using a hash is as balanced as
+ * randomizing coordinates using an rng, for every randomize strategy.
+ */
+
+/// Get the index of the closest node to a coordinate.
+/// Ties are broken by the lowest index.
+fn closest_node(coord: &Vec<u64>, coords: &[Vec<u64>]) -> usize {
+    (0 .. coords.len())
+        .min_by_key(|&i| (max_dist(coord, &coords[i]), i))
+        .unwrap()
+}
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    // We generate num_nodes * iter_mult coordinates:
+    let iter_mult = 1;
+
+    println!("Compare balance of rng coordinates and hashed coordinates.");
+    println!("iter_mult = {}", iter_mult);
+    println!();
+
+    for g in 6 .. 14 { // Iterate over size of network.
+        let l = 2 * g + 1;
+
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                /* Generate network */
+                let seed: &[_] = &[1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 0x10000, 0x20000, &mut network_rng);
+
+                // Amount of landmarks can not be above half of the node count:
+                let mut num_landmarks: usize = g * g;
+                if num_landmarks as f64 > (net.igraph.node_count() as f64) / 2.0 {
+                    num_landmarks = net.igraph.node_count() / 2;
+                }
+                let landmarks = choose_landmarks(&net, num_landmarks, &mut network_rng);
+                let coords = match build_coords(&net, &landmarks) {
+                    Some(coords) => coords,
+                    None => unreachable!(),
+                };
+                let upper_constraints = calc_upper_constraints(&landmarks, &coords);
+                let num_coords = net.igraph.node_count() * iter_mult;
+
+                for (strategy_name, strategy) in RandomizeStrategy::all() {
+                    print!("g={:2}; ",g);
+                    match net_type {
+                        0 => print!("rand    ; "),
+                        1 => print!("2d      ; "),
+                        2 => print!("rand+2d ; "),
+                        3 => print!("planar  ; "),
+                        4 => print!("tree    ; "),
+                        _ => unreachable!(),
+                    }
+                    print!("ni={:1} |",net_iter);
+                    print!("{:15}|", strategy_name);
+
+                    let seed: &[_] = &[2,g,net_type,net_iter];
+                    let mut coord_rng: StdRng = rand::SeedableRng::from_seed(seed);
+
+                    let mut rng_repeats: Vec<usize> = vec![0; net.igraph.node_count()];
+                    let mut hash_repeats: Vec<usize> = vec![0; net.igraph.node_count()];
+                    for key_num in 0 .. num_coords {
+                        let rcoord = randomize_coord_by_strategy(strategy, &upper_constraints,
+                                                                 &landmarks, &coords, &mut coord_rng);
+                        rng_repeats[closest_node(&rcoord, &coords)] += 1;
+
+                        let key = format!("key{}", key_num).into_bytes();
+                        let hcoord = hash_to_coord(&key, strategy, &upper_constraints,
+                                                   &landmarks, &coords);
+                        hash_repeats[closest_node(&hcoord, &coords)] += 1;
+                    }
+
+                    let count_hit = |repeats: &Vec<usize>| repeats.iter().filter(|&&r| r > 0).count();
+                    print!(" rng: max_nr = {:4}, hit = {:6}",
+                           rng_repeats.iter().max().unwrap(), count_hit(&rng_repeats));
+                    print!(" | hash: max_nr = {:4}, hit = {:6}",
+                           hash_repeats.iter().max().unwrap(), count_hit(&hash_repeats));
+                    println!();
+                }
+            }
+        }
+        println!();
+    }
+}
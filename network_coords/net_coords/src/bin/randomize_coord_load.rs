@@ -0,0 +1,111 @@
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+use rand::distributions::{IndependentSample, Range};
+
+use net_coords::landmarks::coords::{build_coords, choose_landmarks};
+use net_coords::landmarks::randomize_coord::{calc_upper_constraints,
+    randomize_coord_by_strategy, RandomizeStrategy};
+use net_coords::landmarks::{gen_areas, find_path_landmarks_areas_by_coord};
+use net_coords::landmarks::load_balance::{analyze_load, LoadReport};
+use net_coords::network_gen::{gen_network};
+
+
+/*
+ * Rank coordinate randomizers by how balanced is the load they create on the
+ * network nodes. Every random coordinate is routed from a random node, and lands
+ * at some node in the network.
+ */
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    // We generate num_nodes * iter_mult random coordinates:
+    let iter_mult = 4;
+    let max_visits = 2;
+    let strategies = [
+        ("rand_coeffs", RandomizeStrategy::RandCoeffs),
+        ("fair_cuts", RandomizeStrategy::FairCuts),
+        ("rw", RandomizeStrategy::Rw),
+        ("rw_sparse", RandomizeStrategy::RwSparse),
+        ("rw_directional", RandomizeStrategy::RwDirectional),
+        ("rw_mix", RandomizeStrategy::RwMix),
+    ];
+
+    println!("Load balance of coordinate randomizers");
+    println!("iter_mult = {}", iter_mult);
+    println!("max_visits = {}", max_visits);
+    println!();
+
+    for g in 6 .. 14 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                /* Generate network */
+                let seed: &[_] = &[1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 0x10000, 0x20000, &mut network_rng);
+                let num_nodes = net.igraph.node_count();
+
+                let amount_close = ((num_nodes as f64).log(2.0) as usize).pow(2);
+                // Amount of landmarks can not be above half of the node count:
+                let mut num_landmarks: usize = g * g;
+                if num_landmarks as f64 > (num_nodes as f64) / 2.0 {
+                    num_landmarks = num_nodes / 2;
+                }
+                let areas = gen_areas(amount_close, &net);
+                let landmarks = choose_landmarks(&net, num_landmarks, &mut network_rng);
+                let coords = match build_coords(&net, &landmarks) {
+                    Some(coords) => coords,
+                    None => unreachable!(),
+                };
+                let upper_constraints = calc_upper_constraints(&landmarks, &coords);
+
+                let mut reports: Vec<(&str, LoadReport)> = Vec::new();
+                for &(strategy_name, strategy) in &strategies {
+                    let seed: &[_] = &[2,g,net_type,net_iter];
+                    let mut coord_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                    let seed: &[_] = &[3,g,net_type,net_iter];
+                    let mut route_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                    let node_range: Range<usize> = Range::new(0, num_nodes);
+
+                    let report = analyze_load(num_nodes, num_nodes * iter_mult,
+                        || randomize_coord_by_strategy(strategy, &upper_constraints,
+                                                       &landmarks, &coords, &mut coord_rng),
+                        |rcoord| {
+                            let src_node = node_range.ind_sample(&mut route_rng);
+                            let (found_node, _, _) = find_path_landmarks_areas_by_coord(
+                                src_node, rcoord, max_visits, &net, &coords, &landmarks,
+                                &areas, &mut route_rng);
+                            found_node
+                        });
+                    reports.push((strategy_name, report));
+                }
+
+                // Rank by Gini coefficient. Most balanced first:
+                reports.sort_by(|a, b| a.1.gini.partial_cmp(&b.1.gini).unwrap());
+                for (rank, &(strategy_name, ref report)) in reports.iter().enumerate() {
+                    print!("g={:2}; ",g);
+                    match net_type {
+                        0 => print!("rand    ; "),
+                        1 => print!("2d      ; "),
+                        2 => print!("rand+2d ; "),
+                        3 => print!("planar  ; "),
+                        4 => print!("tree    ; "),
+                        _ => unreachable!(),
+                    }
+                    print!("ni={:1} |",net_iter);
+                    print!(" rank={:1} {:15}|", rank + 1, strategy_name);
+                    print!(" max/mean = {:7.3} |", report.max_mean_ratio);
+                    print!(" gini = {:5.3} |", report.gini);
+                    print!(" never_hit = {:5.3}", report.never_hit_ratio);
+                    println!();
+                }
+            }
+        }
+        println!();
+    }
+}
@@ -8,10 +8,11 @@ use self::rand::{StdRng};
 // use std::hash::Hash;
 use net_coords::landmarks::coords::{build_coords, choose_landmarks};
 use net_coords::landmarks::{find_path_landmarks_areas_approx, 
-    find_path_landmarks_areas, gen_areas};
+    find_path_landmarks_areas, find_path_landmarks_areas_by_coord, gen_areas};
 use net_coords::network_gen::{gen_network};
 use net_coords::random_util::choose_k_nums;
 use net_coords::landmarks::randomize_coord::{drift_coordinate};
+use net_coords::landmarks::drift::{find_node_last_mile};
 
 use std::collections::HashSet;
 
@@ -29,35 +30,25 @@ fn main() {
     // We generate num_nodes * iter_mult random coordinates:
     let num_pairs = 100;
     let max_visits = 2;
-    let drift_size = 0x8000;
+    let drift_sizes = [0x2000, 0x8000, 0x20000, 0x80000];
 
     println!("Try to find a node given drifted coordinates");
     println!();
     println!("max_visits = {}", max_visits);
     println!("num_pairs = {}", num_pairs);
-    println!("drift_size = {}", drift_size);
+    println!("drift_sizes = {:x?}", drift_sizes);
     println!();
 
     for g in 8 .. 20 { // Iterate over size of network.
         let l = 2 * g + 1;
         for net_type in 0 .. net_types { // Iterate over type of network
             for net_iter in 0 .. net_iters { // Three iterations for each type of network
-                print!("g={:2}; ",g);
-                match net_type {
-                    0 => print!("rand    ; "),
-                    1 => print!("2d      ; "),
-                    2 => print!("rand+2d ; "),
-                    3 => print!("planar  ; "),
-                    _ => unreachable!(),
-                }
-                // print!("nt={:1}; ",net_type);
                 /* Generate network */
                 let seed: &[_] = &[1,g,net_type,net_iter];
                 let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
                 // let net = gen_network(net_type, g, l, 0x10000, 0x20000 , &mut network_rng);
                 // let net = gen_network(net_type, g, l, 0x10000, 0x20000 , &mut network_rng);
                 let net = gen_network(net_type, g, l, 0x10000, 0x20000 , &mut network_rng);
-                print!("ni={:1} |",net_iter);
 
                 // let avg_degree = ((((2*net.igraph.edge_count()) as f64) / 
                 //     (net.igraph.node_count() as f64)) + 1.0) as usize;
@@ -80,57 +71,88 @@ fn main() {
                 };
                 // let upper_constraints = calc_upper_constraints(&landmarks, &coords);
 
-                let mut pair_rng: StdRng = 
-                    rand::SeedableRng::from_seed(&[2,g, net_type, net_iter] as &[_]);
-                let mut coord_rng: StdRng = 
-                    rand::SeedableRng::from_seed(&[3,g, net_type, net_iter] as &[_]);
-                let mut route_rng: StdRng = 
-                    rand::SeedableRng::from_seed(&[4,g, net_type, net_iter] as &[_]);
-
-                let mut num_paths_found: usize = 0;
-                let mut sum_path_len: u64 = 0;
-
-                for _ in 0 .. num_pairs {
-                    // Randomize a pair of nodes.
-                    let mut node_pair = choose_k_nums(2,net.igraph.node_count(),
-                            &mut pair_rng).into_iter().collect::<Vec<usize>>();
-                    // Sort for determinism:
-                    node_pair.sort();
-
-                    // First make sure that node_pair[0] can find node_pair[1].
-                    assert!(find_path_landmarks_areas(node_pair[0], node_pair[1], &net, &coords, &landmarks, 
-                                              &areas, &mut route_rng).is_some());
-
-                    // Drift node_pair[1]'s coordinate:
-                    let mut drifted_coord = coords[node_pair[1]].clone();
-                    drift_coordinate(drift_size, &mut drifted_coord, &mut coord_rng);
-
-                    let mut hs = HashSet::new();
-                    hs.insert(node_pair[1]);
-
-                    let opt_path_len = 
-                        find_path_landmarks_areas_approx(node_pair[0], &hs, &drifted_coord,
-                                   net.igraph.node_count() as u64, &net, 
-                                   &coords, &landmarks, &areas, &mut route_rng);
-
-                    if let Some(path_len) = opt_path_len {
-                        sum_path_len += path_len;
-                        num_paths_found += 1;
-                    } else {
-                        // println!();
-                        // println!("rcoord = {:?}", rcoord);
-                        // println!();
+                for &drift_size in &drift_sizes {
+                    print!("g={:2}; ",g);
+                    match net_type {
+                        0 => print!("rand    ; "),
+                        1 => print!("2d      ; "),
+                        2 => print!("rand+2d ; "),
+                        3 => print!("planar  ; "),
+                        _ => unreachable!(),
+                    }
+                    // print!("nt={:1}; ",net_type);
+                    print!("ni={:1} |",net_iter);
+                    print!(" drift = {:6x} |", drift_size);
+
+                    let mut pair_rng: StdRng = 
+                        rand::SeedableRng::from_seed(&[2,g, net_type, net_iter] as &[_]);
+                    let mut coord_rng: StdRng = 
+                        rand::SeedableRng::from_seed(&[3,g, net_type, net_iter] as &[_]);
+                    let mut route_rng: StdRng = 
+                        rand::SeedableRng::from_seed(&[4,g, net_type, net_iter] as &[_]);
+
+                    let mut num_paths_found: usize = 0;
+                    let mut sum_path_len: u64 = 0;
+                    let mut num_last_mile_found: usize = 0;
+                    let mut sum_last_mile_hops: u64 = 0;
+
+                    for _ in 0 .. num_pairs {
+                        // Randomize a pair of nodes.
+                        let mut node_pair = choose_k_nums(2,net.igraph.node_count(),
+                                &mut pair_rng).into_iter().collect::<Vec<usize>>();
+                        // Sort for determinism:
+                        node_pair.sort();
+
+                        // First make sure that node_pair[0] can find node_pair[1].
+                        assert!(find_path_landmarks_areas(node_pair[0], node_pair[1], &net, &coords, &landmarks, 
+                                                  &areas, &mut route_rng).is_some());
+
+                        // Drift node_pair[1]'s coordinate:
+                        let mut drifted_coord = coords[node_pair[1]].clone();
+                        drift_coordinate(drift_size, &mut drifted_coord, &mut coord_rng);
+
+                        let mut hs = HashSet::new();
+                        hs.insert(node_pair[1]);
+
+                        let opt_path_len = 
+                            find_path_landmarks_areas_approx(node_pair[0], &hs, &drifted_coord,
+                                       net.igraph.node_count() as u64, &net, 
+                                       &coords, &landmarks, &areas, &mut route_rng);
+
+                        if let Some(path_len) = opt_path_len {
+                            sum_path_len += path_len;
+                            num_paths_found += 1;
+                        } else {
+                            // println!();
+                            // println!("rcoord = {:?}", rcoord);
+                            // println!();
+                        }
+
+                        // Route to the best match, and search for node_pair[1] around it:
+                        let last_mile_result = find_node_last_mile(&net, node_pair[1],
+                                       &drifted_coord, amount_close, |coord| {
+                            let (best_match, path_len, _) = find_path_landmarks_areas_by_coord(
+                                node_pair[0], coord, max_visits, &net, &coords, &landmarks,
+                                &areas, &mut route_rng);
+                            (best_match, path_len)
+                        });
+                        if last_mile_result.found {
+                            num_last_mile_found += 1;
+                            sum_last_mile_hops += last_mile_result.last_mile_hops;
+                        }
                     }
-                }
-
-
-                let avg_path_len = (sum_path_len as f64) / (num_paths_found as f64);
-                print!("avg_path_len = {:8.3} |",avg_path_len);
-                let found_ratio = (num_paths_found as f64) / (num_pairs as f64);
-                print!("found_ratio = {:5.3} |",found_ratio);
-
 
-                println!();
+                    let avg_path_len = (sum_path_len as f64) / (num_paths_found as f64);
+                    print!("avg_path_len = {:8.3} |",avg_path_len);
+                    let found_ratio = (num_paths_found as f64) / (num_pairs as f64);
+                    print!("found_ratio = {:5.3} |",found_ratio);
+                    let last_mile_ratio = (num_last_mile_found as f64) / (num_pairs as f64);
+                    print!("last_mile_found = {:5.3} |",last_mile_ratio);
+                    let avg_last_mile_hops = 
+                        (sum_last_mile_hops as f64) / (num_last_mile_found as f64);
+                    print!("last_mile_hops = {:6.3}",avg_last_mile_hops);
+                    println!();
+                }
             }
         }
         println!();
@@ -0,0 +1,129 @@
+/*
+ * Measure the size and verification cost of signed chains for tower distance announcements.
+ * First for chains of growing length, then for tower updates in various networks.
+ */
+
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use std::time::Instant;
+
+use rand::{StdRng};
+use rand::distributions::{IndependentSample, Range};
+
+use net_coords::network_gen::{gen_network};
+use net_coords::towers::{choose_towers,
+                         calc_towers_info,
+                         make_tower_update,
+                         verify_tower_update};
+use net_coords::signed_chain::{SignedChain, gen_key_pair, public_key_bytes};
+
+
+#[cfg(not(test))]
+fn main() {
+    let experiment_seed = 0x1337;
+    let num_verify_iters = 20;
+    let max_age = 60;
+
+    println!("Signed chains of growing length:");
+    println!();
+
+    let seed: &[_] = &[experiment_seed,1];
+    let mut keys_rng: StdRng = rand::SeedableRng::from_seed(seed);
+    let max_chain_len = 256;
+    let key_pairs = (0 .. max_chain_len + 1)
+        .map(|_| gen_key_pair(&mut keys_rng))
+        .collect::<Vec<_>>();
+
+    let mut chain = SignedChain::new(&public_key_bytes(&key_pairs[0]), 0);
+    for i in 1 .. max_chain_len + 1 {
+        chain = chain.extend(&key_pairs[i - 1], &public_key_bytes(&key_pairs[i]));
+        if !i.is_power_of_two() {
+            continue;
+        }
+        let start = Instant::now();
+        for _ in 0 .. num_verify_iters {
+            assert!(chain.verify(0, max_age));
+        }
+        let verify_micros = start.elapsed().as_secs_f64() * 1e6 / (num_verify_iters as f64);
+        println!("len = {:4} | wire_size = {:6} bytes | verify = {:9.1} us",
+                 chain.len(), chain.wire_size(), verify_micros);
+    }
+
+    println!();
+    println!("Tower updates in networks:");
+    println!();
+
+    let net_types = 5;
+    let num_updates = 100;
+    for g in 6 .. 15 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            print!("g={:2}; ",g);
+            match net_type {
+                0 => print!("rand    ; "),
+                1 => print!("2d      ; "),
+                2 => print!("rand+2d ; "),
+                3 => print!("planar  ; "),
+                4 => print!("tree    ; "),
+                _ => unreachable!(),
+            }
+
+            /* Generate network */
+            let seed: &[_] = &[experiment_seed,2,g,net_type];
+            let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+            let net = gen_network(net_type, g, l, 1, 2, &mut network_rng);
+
+            // Choose towers:
+            let seed: &[_] = &[experiment_seed,3,g,net_type];
+            let mut towers_rng: StdRng = rand::SeedableRng::from_seed(seed);
+            let num_colors = g*2;
+            let total_num_towers = (2_u64.pow(g as u32) as f64).sqrt() as usize;
+            let num_towers = 1 + (total_num_towers / num_colors);
+            let chosen_towers = choose_towers(&net, num_towers, num_colors, &mut towers_rng);
+            let towers_info = calc_towers_info(&net, &chosen_towers);
+
+            let seed: &[_] = &[experiment_seed,4,g,net_type];
+            let mut updates_rng: StdRng = rand::SeedableRng::from_seed(seed);
+            let key_pairs = (0 .. net.igraph.node_count())
+                .map(|_| gen_key_pair(&mut updates_rng))
+                .collect::<Vec<_>>();
+            let public_keys = key_pairs.iter().map(public_key_bytes).collect::<Vec<_>>();
+
+            // Send updates from random nodes to random neighbors:
+            let node_range: Range<usize> = Range::new(0, net.igraph.node_count());
+            let color_range: Range<usize> = Range::new(0, num_colors);
+            let mut sum_chain_len: usize = 0;
+            let mut max_chain_len: usize = 0;
+            let mut sum_wire_size: usize = 0;
+            let mut verify_secs: f64 = 0.0;
+            for _ in 0 .. num_updates {
+                let sender = node_range.ind_sample(&mut updates_rng);
+                let neighbors = net.igraph.neighbors(sender).collect::<Vec<usize>>();
+                let nei_range: Range<usize> = Range::new(0, neighbors.len());
+                let receiver = neighbors[nei_range.ind_sample(&mut updates_rng)];
+                let tower_color = color_range.ind_sample(&mut updates_rng);
+
+                let update = make_tower_update(&net, &towers_info, sender, receiver,
+                                               tower_color, &key_pairs, 0).unwrap();
+                let start = Instant::now();
+                assert!(verify_tower_update(&update, receiver, &public_keys, 0, max_age));
+                verify_secs += start.elapsed().as_secs_f64();
+
+                sum_chain_len += update.chain.len();
+                max_chain_len = max_chain_len.max(update.chain.len());
+                sum_wire_size += update.chain.wire_size();
+            }
+
+            print!("chain len avg = {:6.2} max = {:4} |",
+                   (sum_chain_len as f64) / (num_updates as f64), max_chain_len);
+            print!(" wire_size avg = {:8.1} bytes |",
+                   (sum_wire_size as f64) / (num_updates as f64));
+            print!(" verify avg = {:8.1} us",
+                   verify_secs * 1e6 / (num_updates as f64));
+            println!();
+        }
+        println!();
+    }
+}
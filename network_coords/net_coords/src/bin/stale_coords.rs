@@ -0,0 +1,142 @@
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+
+use net_coords::landmarks::coords::{build_coords, choose_landmarks};
+use net_coords::landmarks::{gen_areas, find_path_landmarks_areas_by_coord};
+use net_coords::landmarks::drift::{change_topology, coords_staleness, find_node_last_mile,
+    StalenessHistogram};
+use net_coords::network_gen::{gen_network};
+use net_coords::random_util::choose_k_nums;
+
+
+/*
+ * Nodes advertise their coordinates, but the network topology keeps changing.
+ * Check how well we can find a node using its stale advertised coordinate,
+ * with and without a last mile search around the best match.
+ */
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_pairs = 100;
+    let max_visits = 2;
+    // Amount of topology changes, as a multiple of g:
+    let change_mults = [0, 1, 4, 16];
+    // Staleness histogram bucket size. About the length of one edge:
+    let bucket_size = 0x10000;
+    let num_shown_buckets = 4;
+
+    println!("Find nodes using stale coordinates after topology changes");
+    println!();
+    println!("max_visits = {}", max_visits);
+    println!("num_pairs = {}", num_pairs);
+    println!("bucket_size = {:x}", bucket_size);
+    println!();
+
+    for g in 8 .. 20 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                for &change_mult in &change_mults {
+                    print!("g={:2}; ",g);
+                    match net_type {
+                        0 => print!("rand    ; "),
+                        1 => print!("2d      ; "),
+                        2 => print!("rand+2d ; "),
+                        3 => print!("planar  ; "),
+                        4 => print!("tree    ; "),
+                        _ => unreachable!(),
+                    }
+                    print!("ni={:1} |",net_iter);
+                    print!(" changes = {:4} |", change_mult * g);
+
+                    /* Generate network */
+                    let seed: &[_] = &[1,g,net_type,net_iter];
+                    let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                    let mut net = gen_network(net_type, g, l, 0x10000, 0x20000, &mut network_rng);
+                    let num_nodes = net.igraph.node_count();
+
+                    let amount_close = ((num_nodes as f64).log(2.0) as usize).pow(2);
+                    // Amount of landmarks can not be above half of the node count:
+                    let mut num_landmarks: usize = g * g;
+                    if num_landmarks as f64 > (num_nodes as f64) / 2.0 {
+                        num_landmarks = num_nodes / 2;
+                    }
+                    let landmarks = choose_landmarks(&net, num_landmarks, &mut network_rng);
+                    // Coordinates advertised before the topology changes:
+                    let advertised_coords = match build_coords(&net, &landmarks) {
+                        Some(coords) => coords,
+                        None => unreachable!(),
+                    };
+
+                    let seed: &[_] = &[2,g,net_type,net_iter];
+                    let mut change_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                    change_topology(&mut net, change_mult * g, 0x10000, 0x20000, &mut change_rng);
+
+                    let areas = gen_areas(amount_close, &net);
+                    let coords = match build_coords(&net, &landmarks) {
+                        Some(coords) => coords,
+                        None => unreachable!(),
+                    };
+                    let staleness = coords_staleness(&advertised_coords, &coords);
+                    let moved_ratio = (staleness.iter().filter(|&&s| s > 0).count() as f64) /
+                        (num_nodes as f64);
+                    let avg_staleness = (staleness.iter().sum::<u64>() as f64) /
+                        (num_nodes as f64);
+
+                    let mut pair_rng: StdRng =
+                        rand::SeedableRng::from_seed(&[3,g, net_type, net_iter] as &[_]);
+                    let mut route_rng: StdRng =
+                        rand::SeedableRng::from_seed(&[4,g, net_type, net_iter] as &[_]);
+
+                    let mut direct_histogram = StalenessHistogram::new(bucket_size);
+                    let mut last_mile_histogram = StalenessHistogram::new(bucket_size);
+                    for _ in 0 .. num_pairs {
+                        // Randomize a pair of nodes.
+                        let mut node_pair = choose_k_nums(2, num_nodes, &mut pair_rng)
+                            .into_iter().collect::<Vec<usize>>();
+                        // Sort for determinism:
+                        node_pair.sort();
+                        let (src_node, dst_node) = (node_pair[0], node_pair[1]);
+
+                        let mut route = |coord: &Vec<u64>| {
+                            let (best_match, path_len, _) = find_path_landmarks_areas_by_coord(
+                                src_node, coord, max_visits, &net, &coords, &landmarks,
+                                &areas, &mut route_rng);
+                            (best_match, path_len)
+                        };
+
+                        // Without a last mile search:
+                        let result = find_node_last_mile(&net, dst_node,
+                                &advertised_coords[dst_node], 0, &mut route);
+                        direct_histogram.add(staleness[dst_node], result.found);
+
+                        // With a last mile search:
+                        let result = find_node_last_mile(&net, dst_node,
+                                &advertised_coords[dst_node], amount_close, &mut route);
+                        last_mile_histogram.add(staleness[dst_node], result.found);
+                    }
+
+                    print!(" moved = {:5.3} |", moved_ratio);
+                    print!(" avg_staleness = {:9.1} |", avg_staleness);
+                    print!(" found = {:5.3} |", direct_histogram.total_success_ratio());
+                    print!(" last_mile_found = {:5.3} |", last_mile_histogram.total_success_ratio());
+                    // Success of the last mile search by staleness:
+                    print!(" by_staleness =");
+                    for bucket in 0 .. num_shown_buckets {
+                        match last_mile_histogram.success_ratio(bucket) {
+                            Some(ratio) => print!(" {:5.3}", ratio),
+                            None => print!("   -  "),
+                        }
+                    }
+                    println!();
+                }
+            }
+        }
+        println!();
+    }
+}
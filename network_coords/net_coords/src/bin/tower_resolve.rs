@@ -0,0 +1,126 @@
+/*
+ * Simulate a resolve service (hashNodeId -> nodeId) given by towers.
+ * Every node registers with the towers responsible for its hash (One of every color), and then
+ * random nodes look up the hashes of random destinations, through the closest responsible
+ * tower. Popular destinations are looked up more often, so that local towers may answer from
+ * their cache.
+ * Measure registration path length (Total over all colors), lookup path length, cache hit rate
+ * and storage load of towers.
+ */
+
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng, Rng};
+use rand::distributions::{IndependentSample, Range};
+
+use net_coords::network_gen::{gen_network};
+use net_coords::towers::{choose_towers,
+                         calc_towers_info,
+                         is_towers_info_filled,
+                         TowersOverlay};
+use net_coords::tower_resolve::{ResolveService, hash_node_id};
+
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_lookups = 2000;
+    let cache_capacity = 32;
+    let experiment_seed = 0x1337;
+
+    println!("Resolve service given by towers (cache capacity = {})", cache_capacity);
+    println!();
+
+    for g in 6 .. 13 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                print!("g={:2}; ",g);
+                match net_type {
+                    0 => print!("rand    ; "),
+                    1 => print!("2d      ; "),
+                    2 => print!("rand+2d ; "),
+                    3 => print!("planar  ; "),
+                    4 => print!("tree    ; "),
+                    _ => unreachable!(),
+                }
+                print!("ni={:1} |",net_iter);
+
+                /* Generate network */
+                let seed: &[_] = &[experiment_seed,1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 1, 2, &mut network_rng);
+                // Makes sure that the resulting network is connected:
+                assert!(net.is_connected());
+                let num_nodes = net.igraph.node_count();
+
+                // Choose towers:
+                let seed: &[_] = &[experiment_seed,2,g,net_type,net_iter];
+                let mut towers_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let num_colors = g*2;
+                let total_num_towers = (2_u64.pow(g as u32) as f64).sqrt() as usize;
+                let num_towers = 1 + (total_num_towers / num_colors);
+                let chosen_towers = choose_towers(&net, num_towers, num_colors, &mut towers_rng);
+                let towers_info = calc_towers_info(&net, &chosen_towers);
+                assert!(is_towers_info_filled(&towers_info));
+                let overlay = TowersOverlay::new(&chosen_towers, &towers_info);
+
+                // Register all nodes:
+                let mut service = ResolveService::new(&chosen_towers, cache_capacity);
+                let mut sum_register_len: u64 = 0;
+                let mut num_registered: usize = 0;
+                for node in 0 .. num_nodes {
+                    if let Some(register_len) = service.register(node, &towers_info, &overlay) {
+                        sum_register_len += register_len;
+                        num_registered += 1;
+                    }
+                }
+
+                // Look up random destinations. Destinations with small ids are more popular:
+                let seed: &[_] = &[experiment_seed,3,g,net_type,net_iter];
+                let mut lookups_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let node_range: Range<usize> = Range::new(0, num_nodes);
+                let mut num_found: usize = 0;
+                let mut num_hits: usize = 0;
+                let mut sum_lookup_len: u64 = 0;
+                for _ in 0 .. num_lookups {
+                    let src_node = node_range.ind_sample(&mut lookups_rng);
+                    let popularity: f64 = lookups_rng.gen();
+                    let dst_node = ((num_nodes as f64) * popularity.powi(3)) as usize;
+                    if let Some(result) = service.lookup(src_node, hash_node_id(dst_node),
+                                                         &towers_info, &overlay) {
+                        assert!(result.node == dst_node);
+                        num_found += 1;
+                        sum_lookup_len += result.path_len;
+                        if result.cache_hit {
+                            num_hits += 1;
+                        }
+                    }
+                }
+
+                let mut towers = chosen_towers.iter().flatten().cloned().collect::<Vec<usize>>();
+                towers.sort();
+                towers.dedup();
+                let storage_load = service.storage_load(&towers);
+                let avg_load = (storage_load.iter().sum::<usize>() as f64) /
+                    (towers.len() as f64);
+
+                print!(" registered = {:5.3} len avg = {:6.2} |",
+                       (num_registered as f64) / (num_nodes as f64),
+                       (sum_register_len as f64) / (num_registered as f64));
+                print!(" found = {:5.3} len avg = {:6.2} hit rate = {:5.3} |",
+                       (num_found as f64) / (num_lookups as f64),
+                       (sum_lookup_len as f64) / (num_found as f64),
+                       (num_hits as f64) / (num_found as f64));
+                print!(" storage avg = {:7.1} max = {:6}",
+                       avg_load, storage_load.iter().max().unwrap());
+
+                println!();
+            }
+        }
+        println!();
+    }
+}
@@ -0,0 +1,79 @@
+/*
+ * Measure the bandwidth required for towers maintenance:
+ * - Initial convergence of local towers info (Per node).
+ * - Convergence of the distance vectors between towers (Per tower).
+ * Entries per tower are divided by sqrt(n), to check if they scale like sqrt(n).
+ */
+
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+
+use net_coords::network_gen::{gen_network};
+use net_coords::towers::{choose_towers,
+                         calc_towers_info_bandwidth,
+                         calc_towers_dist_vectors,
+                         is_towers_info_filled};
+
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let experiment_seed = 0x1337;
+
+    println!("Bandwidth of towers maintenance");
+    println!();
+
+    for g in 6 .. 15 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        let sqrt_n = (2_u64.pow(g as u32) as f64).sqrt();
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                print!("g={:2}; ",g);
+                match net_type {
+                    0 => print!("rand    ; "),
+                    1 => print!("2d      ; "),
+                    2 => print!("rand+2d ; "),
+                    3 => print!("planar  ; "),
+                    4 => print!("tree    ; "),
+                    _ => unreachable!(),
+                }
+                print!("ni={:1} |",net_iter);
+
+                /* Generate network */
+                let seed: &[_] = &[experiment_seed,1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 1, 2, &mut network_rng);
+                // Makes sure that the resulting network is connected:
+                assert!(net.is_connected());
+
+                // Choose towers:
+                let seed: &[_] = &[experiment_seed,2,g,net_type,net_iter];
+                let mut towers_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let num_colors = g*2;
+                let num_towers = 1 + (sqrt_n as usize / num_colors);
+                let chosen_towers = choose_towers(&net, num_towers, num_colors, &mut towers_rng);
+
+                let (towers_info, node_bandwidth) =
+                    calc_towers_info_bandwidth(&net, &chosen_towers);
+                assert!(is_towers_info_filled(&towers_info));
+                let (_, _, tower_bandwidth) = calc_towers_dist_vectors(&chosen_towers,
+                                                                       &towers_info);
+
+                print!(" nodes: rounds = {:3} msgs avg = {:7.1} entries avg = {:7.1} max = {:6} |",
+                       node_bandwidth.num_rounds, node_bandwidth.avg_messages(),
+                       node_bandwidth.avg_entries(), node_bandwidth.max_entries());
+                print!(" towers: rounds = {:3} msgs avg = {:6.1} entries avg = {:7.1} max = {:6}",
+                       tower_bandwidth.num_rounds, tower_bandwidth.avg_messages(),
+                       tower_bandwidth.avg_entries(), tower_bandwidth.max_entries());
+                print!(" | entries avg / sqrt(n) = {:6.2}", tower_bandwidth.avg_entries() / sqrt_n);
+
+                println!();
+            }
+        }
+        println!();
+    }
+}
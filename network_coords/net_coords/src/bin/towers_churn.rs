@@ -0,0 +1,114 @@
+/*
+ * Simulate failures of towers and election of replacement towers.
+ * Measure the cost of incrementally re-propagating towers info:
+ * Messages, convergence rounds and rounds in which some nodes have no local tower.
+ * A failed tower sends a removal notice that spreads along with the re-propagated information.
+ * Nodes ignore information about the failed tower only after receiving the notice. The amount
+ * of nodes that received the notice is measured too.
+ */
+
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+use rand::distributions::{IndependentSample, Range};
+
+use net_coords::network_gen::{gen_network};
+use net_coords::towers::{choose_towers,
+                         calc_towers_info,
+                         is_towers_info_filled,
+                         add_tower,
+                         remove_tower,
+                         TowersChangeStats};
+
+
+/// Format average statistics of a list of changes.
+fn format_avg_stats(stats: &[TowersChangeStats]) -> String {
+    let num_stats = stats.len() as f64;
+    format!("msgs = {:9.1} rounds = {:5.1} unfilled = {:5.1} notified = {:7.1}",
+        (stats.iter().map(|s| s.num_messages).sum::<usize>() as f64) / num_stats,
+        (stats.iter().map(|s| s.num_rounds).sum::<usize>() as f64) / num_stats,
+        (stats.iter().map(|s| s.num_unfilled_rounds).sum::<usize>() as f64) / num_stats,
+        (stats.iter().map(|s| s.num_notified).sum::<usize>() as f64) / num_stats)
+}
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_failures = 16;
+    let experiment_seed = 0x1337;
+
+    println!("Failure and re-election of towers");
+    println!();
+
+    for g in 6 .. 15 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                print!("g={:2}; ",g);
+                match net_type {
+                    0 => print!("rand    ; "),
+                    1 => print!("2d      ; "),
+                    2 => print!("rand+2d ; "),
+                    3 => print!("planar  ; "),
+                    4 => print!("tree    ; "),
+                    _ => unreachable!(),
+                }
+                print!("ni={:1} |",net_iter);
+
+                /* Generate network */
+                let seed: &[_] = &[experiment_seed,1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 1, 2, &mut network_rng);
+                // Makes sure that the resulting network is connected:
+                assert!(net.is_connected());
+
+                // Choose towers:
+                let seed: &[_] = &[experiment_seed,2,g,net_type,net_iter];
+                let mut towers_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let num_colors = g*2;
+                let total_num_towers = (2_u64.pow(g as u32) as f64).sqrt() as usize;
+                let num_towers = 1 + (total_num_towers / num_colors);
+                let mut chosen_towers = choose_towers(&net, num_towers, num_colors,
+                                                      &mut towers_rng);
+                let mut towers_info = calc_towers_info(&net, &chosen_towers);
+                assert!(is_towers_info_filled(&towers_info));
+
+                // Fail random towers, and elect a random replacement for every failed tower:
+                let seed: &[_] = &[experiment_seed,3,g,net_type,net_iter];
+                let mut churn_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let color_range: Range<usize> = Range::new(0, num_colors);
+                let node_range: Range<usize> = Range::new(0, net.igraph.node_count());
+                let mut remove_stats = Vec::new();
+                let mut add_stats = Vec::new();
+                for _ in 0 .. num_failures {
+                    let tower_color = color_range.ind_sample(&mut churn_rng);
+                    let tower_range: Range<usize> =
+                        Range::new(0, chosen_towers[tower_color].len());
+                    let tower_node = chosen_towers[tower_color]
+                        [tower_range.ind_sample(&mut churn_rng)];
+                    remove_stats.push(remove_tower(&net, &mut chosen_towers, &mut towers_info,
+                                                   tower_color, tower_node));
+
+                    let new_tower_node = loop {
+                        let node = node_range.ind_sample(&mut churn_rng);
+                        if chosen_towers[tower_color].binary_search(&node).is_err() {
+                            break node;
+                        }
+                    };
+                    add_stats.push(add_tower(&net, &mut chosen_towers, &mut towers_info,
+                                             tower_color, new_tower_node));
+                }
+                assert!(is_towers_info_filled(&towers_info));
+
+                print!(" fail: {} |", format_avg_stats(&remove_stats));
+                print!(" elect: {}", format_avg_stats(&add_stats));
+
+                println!();
+            }
+        }
+        println!();
+    }
+}
@@ -0,0 +1,157 @@
+/*
+ * Simulate towers that volunteer as towers but drop the traffic they should forward
+ * (Free riders). Every tower that forwards a message acknowledges it to the source node, so
+ * the source node learns which tower has dropped the message. Nodes track the reliability of
+ * the towers their messages went through, switch to other local towers of the same color,
+ * and route around unreliable towers in the middle of the overlay path.
+ * Messages are sent in epochs (Every node sends one message to a random destination in
+ * every epoch). For every epoch, measure the delivery rate, and the ratio of messages that went
+ * through a free rider tower. Measure also the amount of (node, color) pairs using a free rider
+ * as a local tower. Nodes that never switch towers are measured too, for comparison.
+ * Networks start from g=8, so that there are at least two towers of every color to switch
+ * between.
+ */
+
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+use rand::distributions::{IndependentSample, Range};
+
+use net_coords::network_gen::{gen_network};
+use net_coords::towers::{choose_towers,
+                         calc_towers_info,
+                         is_towers_info_filled,
+                         repair_overlay,
+                         TowersOverlay};
+use net_coords::tower_incentive::{FreeRiders, TowersReliability, send_message};
+
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_epochs = 16;
+    let num_candidates = 3;
+    let learning_rate = 0.5;
+    let switch_threshold = 0.3;
+    let drop_prob = 0.8;
+    let fractions = [0.0, 0.1, 0.3];
+    // Epochs in which delivery rate is printed:
+    let print_epochs = [0, 1, 2, 4, 8, 15];
+    let experiment_seed = 0x1337;
+
+    println!("Free rider towers (drop_prob = {})", drop_prob);
+    println!();
+
+    for g in 8 .. 13 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+
+                /* Generate network */
+                let seed: &[_] = &[experiment_seed,1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 1, 2, &mut network_rng);
+                // Makes sure that the resulting network is connected:
+                assert!(net.is_connected());
+                let num_nodes = net.igraph.node_count();
+
+                // Choose towers:
+                let seed: &[_] = &[experiment_seed,2,g,net_type,net_iter];
+                let mut towers_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let num_colors = g*2;
+                let total_num_towers = (2_u64.pow(g as u32) as f64).sqrt() as usize;
+                let num_towers = 1 + (total_num_towers / num_colors);
+                let chosen_towers = choose_towers(&net, num_towers, num_colors, &mut towers_rng);
+                let towers_info = calc_towers_info(&net, &chosen_towers);
+                assert!(is_towers_info_filled(&towers_info));
+                // Repair the overlay, so that messages are lost only because of free riders:
+                let mut overlay = TowersOverlay::new(&chosen_towers, &towers_info);
+                repair_overlay(&net, &mut overlay);
+
+                for (fi, &fraction) in fractions.iter().enumerate() {
+                    print!("g={:2}; ",g);
+                    match net_type {
+                        0 => print!("rand    ; "),
+                        1 => print!("2d      ; "),
+                        2 => print!("rand+2d ; "),
+                        3 => print!("planar  ; "),
+                        4 => print!("tree    ; "),
+                        _ => unreachable!(),
+                    }
+                    print!("ni={:1} |",net_iter);
+                    print!(" free={:4.2} |", fraction);
+
+                    let seed: &[_] = &[experiment_seed,3,g,net_type,net_iter,fi];
+                    let mut sim_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                    let free_riders = FreeRiders::new(&chosen_towers, fraction, drop_prob,
+                                                      &mut sim_rng);
+                    // Nodes that never switch towers:
+                    let mut static_reliability = TowersReliability::new(
+                        &net, &chosen_towers, num_candidates, learning_rate, 0.0);
+                    let mut adaptive_reliability = TowersReliability::new(
+                        &net, &chosen_towers, num_candidates, learning_rate, switch_threshold);
+                    let bad_before = adaptive_reliability
+                        .count_free_rider_preferences(&free_riders);
+
+                    let node_range: Range<usize> = Range::new(0, num_nodes);
+                    let mut static_delivered: usize = 0;
+                    let mut static_sent: usize = 0;
+                    let mut epoch_delivery = Vec::new();
+                    let mut epoch_via_free_riders = Vec::new();
+                    for _ in 0 .. num_epochs {
+                        let mut delivered: usize = 0;
+                        let mut via_free_riders: usize = 0;
+                        let mut sent: usize = 0;
+                        for src_node in 0 .. num_nodes {
+                            let dst_node = node_range.ind_sample(&mut sim_rng);
+                            if dst_node == src_node {
+                                continue;
+                            }
+                            let delivery = send_message(src_node, dst_node,
+                                &mut adaptive_reliability, &overlay, &free_riders,
+                                &mut sim_rng).unwrap();
+                            sent += 1;
+                            if delivery.delivered {
+                                delivered += 1;
+                            }
+                            if delivery.path.iter().any(|&tower_node| tower_node != src_node &&
+                                    tower_node != dst_node &&
+                                    free_riders.is_free_rider(tower_node)) {
+                                via_free_riders += 1;
+                            }
+                            let delivery = send_message(src_node, dst_node,
+                                &mut static_reliability, &overlay, &free_riders,
+                                &mut sim_rng).unwrap();
+                            static_sent += 1;
+                            if delivery.delivered {
+                                static_delivered += 1;
+                            }
+                        }
+                        epoch_delivery.push((delivered as f64) / (sent as f64));
+                        epoch_via_free_riders.push((via_free_riders as f64) / (sent as f64));
+                    }
+                    let bad_after = adaptive_reliability
+                        .count_free_rider_preferences(&free_riders);
+
+                    print!(" static: delivery = {:5.3} |",
+                           (static_delivered as f64) / (static_sent as f64));
+                    print!(" switching: delivery by epoch =");
+                    for &epoch in &print_epochs {
+                        print!(" {:5.3}", epoch_delivery[epoch]);
+                    }
+                    print!(" | via free riders = {:5.3} -> {:5.3}",
+                           epoch_via_free_riders[0], epoch_via_free_riders[num_epochs - 1]);
+                    print!(" | switches/node = {:5.2}",
+                           (adaptive_reliability.num_switches() as f64) / (num_nodes as f64));
+                    print!(" | free rider prefs = {:6} -> {:6}", bad_before, bad_after);
+
+                    println!();
+                }
+            }
+        }
+        println!();
+    }
+}
@@ -0,0 +1,107 @@
+/*
+ * Route packets between random pairs of nodes through the towers overlay.
+ * Report the amount of hops and the stretch (Route length divided by shortest path length)
+ * in various networks.
+ */
+
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+
+use net_coords::network_gen::{gen_network};
+use net_coords::random_util::choose_k_nums;
+use net_coords::towers::{choose_towers,
+                         calc_towers_info,
+                         is_towers_info_filled,
+                         TowersOverlay,
+                         find_path_towers};
+
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_routes = 200;
+    let experiment_seed = 0x1337;
+
+    println!("Routing through the local towers overlay graph");
+    println!();
+
+    for g in 6 .. 21 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                print!("g={:2}; ",g);
+                match net_type {
+                    0 => print!("rand    ; "),
+                    1 => print!("2d      ; "),
+                    2 => print!("rand+2d ; "),
+                    3 => print!("planar  ; "),
+                    4 => print!("tree    ; "),
+                    _ => unreachable!(),
+                }
+                print!("ni={:1} |",net_iter);
+
+                /* Generate network */
+                let seed: &[_] = &[experiment_seed,1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 1, 2, &mut network_rng);
+                // Makes sure that the resulting network is connected:
+                assert!(net.is_connected());
+
+                // Choose towers:
+                let seed: &[_] = &[experiment_seed,2,g,net_type,net_iter];
+                let mut towers_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let num_colors = g*2;
+                let total_num_towers = (2_u64.pow(g as u32) as f64).sqrt() as usize;
+                let num_towers = 1 + (total_num_towers / num_colors);
+                let chosen_towers = choose_towers(&net, num_towers, num_colors, &mut towers_rng);
+                let towers_info = calc_towers_info(&net, &chosen_towers);
+                // Make sure that towers_info are valid:
+                assert!(is_towers_info_filled(&towers_info));
+                let overlay = TowersOverlay::new(&chosen_towers, &towers_info);
+
+                // Route between random pairs of nodes:
+                let seed: &[_] = &[experiment_seed,3,g,net_type,net_iter];
+                let mut node_pair_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let mut num_found: usize = 0;
+                let mut sum_hops: u64 = 0;
+                let mut max_hops: u64 = 0;
+                let mut sum_stretch: f64 = 0.0;
+                let mut max_stretch: f64 = 0.0;
+                for _ in 0 .. num_routes {
+                    let mut node_pair = choose_k_nums(2, net.igraph.node_count(),
+                                          &mut node_pair_rng).into_iter().collect::<Vec<usize>>();
+                    // Sort for determinism:
+                    node_pair.sort();
+                    let (src_node, dst_node) = (node_pair[0], node_pair[1]);
+
+                    if let Some(route) = find_path_towers(src_node, dst_node,
+                                                          &towers_info, &overlay) {
+                        let hops = route.total_len();
+                        // All weights are 1, so the distance is measured in hops:
+                        let stretch = (hops as f64) /
+                            (net.dist(src_node, dst_node).unwrap() as f64);
+                        num_found += 1;
+                        sum_hops += hops;
+                        max_hops = max_hops.max(hops);
+                        sum_stretch += stretch;
+                        max_stretch = max_stretch.max(stretch);
+                    }
+                }
+
+                print!("num_towers = {:5} |", num_towers * num_colors);
+                print!(" success = {:4.2} |", (num_found as f64) / (num_routes as f64));
+                print!(" hops avg = {:7.2} max = {:5} |",
+                       (sum_hops as f64) / (num_found as f64), max_hops);
+                print!(" stretch avg = {:5.2} max = {:5.2}",
+                       sum_stretch / (num_found as f64), max_stretch);
+
+                println!();
+            }
+        }
+        println!();
+    }
+}
@@ -1,6 +1,8 @@
 /*
  * Check if the local towers connections form a 
  * strongly connected directed overlay graph in various networks.
+ * Compare towers with colors against indexers, where every node knows its k closest indexers.
+ * Count the extra links between towers required to make the overlay strongly connected.
  */
 
 #![cfg(not(test))]
@@ -12,9 +14,10 @@ use rand::{StdRng};
 
 use net_coords::network_gen::{gen_network};
 use net_coords::towers::{choose_towers, 
-                         calc_towers_info, 
-                         is_connected,
-                         is_towers_info_filled};
+                         calc_towers_info_compact, 
+                         choose_indexers,
+                         calc_indexers_info,
+                         TowersOverlay};
 
 
 #[cfg(not(test))]
@@ -22,8 +25,10 @@ fn main() {
     let net_types = 5;
     let net_iters = 2;
     let experiment_seed = 0x1337;
+    let max_indexers_g = 16;
 
     println!("Checking if local towers overlay graph is strongly connected");
+    println!("Colors: closest tower of every color. Indexers: k = num_colors closest indexers.");
     println!();
 
     for g in 6 .. 21 { // Iterate over size of network.
@@ -56,26 +61,45 @@ fn main() {
                 let num_towers = 1 + (total_num_towers / num_colors);
                 let chosen_towers = choose_towers(&net, num_towers, num_colors, &mut towers_rng);
                 // let chosen_towers = choose_towers(&net, 1, num_colors, &mut towers_rng); // Sanity check
-                let towers_info = calc_towers_info(&net, &chosen_towers);
+                let towers_info = calc_towers_info_compact(&net, &chosen_towers);
                 // Make sure that towers_info are valid:
-                assert!(is_towers_info_filled(&towers_info));
+                assert!(towers_info.is_filled());
 
                 print!("num_colors = {:5} |", num_colors);
                 print!("num_towers = {:5} |", num_towers);
 
-                let (connected, sconnected) = is_connected(&chosen_towers, &towers_info);
-                print!(" connected = ");
-                if connected {
-                    print!("V");
-                } else {
-                    print!("X");
+                let mut overlays = vec![("colors", 
+                                         TowersOverlay::new_compact(&chosen_towers, &towers_info))];
+
+                // Choose the same amount of indexers, without colors.
+                // Indexers info is not compact, so we check only smaller networks:
+                if g <= max_indexers_g {
+                    let seed: &[_] = &[experiment_seed,3,g,net_type,net_iter];
+                    let mut indexers_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                    let indexers = choose_indexers(&net, num_towers * num_colors, 
+                                                   &mut indexers_rng);
+                    let indexers_info = calc_indexers_info(&net, &indexers, num_colors);
+                    overlays.push(("indexers", 
+                                   TowersOverlay::new_indexers(&indexers, &indexers_info)));
                 }
 
-                print!(" | sconnected = ");
-                if sconnected {
-                    print!("V");
-                } else {
-                    print!("X");
+                for &(name, ref overlay) in &overlays {
+                    let (connected, sconnected) = overlay.is_connected();
+                    print!(" {:8}: connected = ", name);
+                    if connected {
+                        print!("V");
+                    } else {
+                        print!("X");
+                    }
+
+                    print!(" sconnected = ");
+                    if sconnected {
+                        print!("V");
+                    } else {
+                        print!("X");
+                    }
+                    print!(" extra_links = {:4}", overlay.strong_connectivity_links().len());
+                    print!(" |");
                 }
 
                 println!();
@@ -0,0 +1,119 @@
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+use rand::distributions::{IndependentSample, Range};
+
+use net_coords::landmarks::coords::{build_coords, choose_landmarks};
+use net_coords::landmarks::randomize_coord::{calc_upper_constraints,
+    randomize_coord_rw_mix};
+use net_coords::landmarks::valleys::{valley_census_by_node, valley_census_by_coord,
+    ValleyCensus};
+use net_coords::network_gen::{gen_network};
+
+
+/*
+ * Count the valleys (greedy local minima) of network coordinates, for destination
+ * nodes and for random destination coordinates.
+ * Report the area size needed to escape every valley, to help tuning amount_close.
+ */
+
+/// Accumulated valley statistics over many destinations.
+#[derive(Default)]
+struct CensusStats {
+    num_censuses: usize,
+    sum_false_valleys: usize,
+    sum_trapped_ratio: f64,
+    max_escape_area: usize,
+    max_escape_radius: u64,
+}
+
+impl CensusStats {
+    fn add(&mut self, census: &ValleyCensus) {
+        self.num_censuses += 1;
+        self.sum_false_valleys += census.false_valleys().len();
+        self.sum_trapped_ratio += census.trapped_ratio();
+        self.max_escape_area = self.max_escape_area.max(census.max_escape_area());
+        self.max_escape_radius = self.max_escape_radius.max(census.max_escape_radius());
+    }
+
+    fn print(&self) {
+        let num_censuses = self.num_censuses as f64;
+        print!(" false_valleys = {:7.3},", (self.sum_false_valleys as f64) / num_censuses);
+        print!(" trapped = {:5.3},", self.sum_trapped_ratio / num_censuses);
+        print!(" max_area = {:5},", self.max_escape_area);
+        print!(" max_radius = {:2}", self.max_escape_radius);
+    }
+}
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_dsts = 8;
+
+    println!("Valley census of network coordinates");
+    println!("num_dsts = {}", num_dsts);
+    println!();
+
+    for g in 6 .. 14 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                print!("g={:2}; ",g);
+                match net_type {
+                    0 => print!("rand    ; "),
+                    1 => print!("2d      ; "),
+                    2 => print!("rand+2d ; "),
+                    3 => print!("planar  ; "),
+                    4 => print!("tree    ; "),
+                    _ => unreachable!(),
+                }
+                print!("ni={:1} |",net_iter);
+
+                /* Generate network */
+                let seed: &[_] = &[1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 0x10000, 0x20000, &mut network_rng);
+                let num_nodes = net.igraph.node_count();
+
+                let amount_close = ((num_nodes as f64).log(2.0) as usize).pow(2);
+                // Amount of landmarks can not be above half of the node count:
+                let mut num_landmarks: usize = g * g;
+                if num_landmarks as f64 > (num_nodes as f64) / 2.0 {
+                    num_landmarks = num_nodes / 2;
+                }
+                let landmarks = choose_landmarks(&net, num_landmarks, &mut network_rng);
+                let coords = match build_coords(&net, &landmarks) {
+                    Some(coords) => coords,
+                    None => unreachable!(),
+                };
+                let upper_constraints = calc_upper_constraints(&landmarks, &coords);
+
+                let seed: &[_] = &[2,g,net_type,net_iter];
+                let mut dst_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let node_range: Range<usize> = Range::new(0, num_nodes);
+
+                let mut node_stats = CensusStats::default();
+                let mut coord_stats = CensusStats::default();
+                for _ in 0 .. num_dsts {
+                    let dst_node = node_range.ind_sample(&mut dst_rng);
+                    node_stats.add(&valley_census_by_node(&net, &coords, &landmarks, dst_node));
+
+                    let dst_coord = randomize_coord_rw_mix(&upper_constraints, &landmarks,
+                                                           &coords, &mut dst_rng);
+                    coord_stats.add(&valley_census_by_coord(&net, &coords, &dst_coord));
+                }
+
+                print!(" amount_close = {:4} |", amount_close);
+                print!(" node:");
+                node_stats.print();
+                print!(" | coord:");
+                coord_stats.print();
+                println!();
+            }
+        }
+        println!();
+    }
+}
@@ -0,0 +1,125 @@
+#![cfg(not(test))]
+extern crate net_coords;
+extern crate rand;
+
+use rand::{StdRng};
+
+use net_coords::landmarks::coords::{build_coords, choose_landmarks};
+use net_coords::landmarks::coord_mappers::{approx_max_dist};
+use net_coords::network_gen::{gen_network};
+use net_coords::random_util::choose_k_nums;
+use net_coords::vivaldi::{init_vivaldi, choose_peers, converge_vivaldi, vivaldi_rel_errors,
+    find_path_greedy, VivaldiParams};
+
+
+/*
+ * Compare Vivaldi coordinates (Euclidean plus height) with landmarks coordinates.
+ * Check the convergence of Vivaldi, its distance prediction error,
+ * and greedy routing success of both coordinate systems.
+ */
+
+#[cfg(not(test))]
+fn main() {
+    let net_types = 5;
+    let net_iters = 2;
+    let num_pairs = 100;
+    let num_remote_peers = 8;
+    let checkpoints = [10, 50, 200];
+    let params = VivaldiParams {
+        dims: 3,
+        cc: 0.25,
+        ce: 0.25,
+    };
+
+    println!("Compare Vivaldi coordinates with landmarks coordinates");
+    println!();
+    println!("num_pairs = {}", num_pairs);
+    println!("num_remote_peers = {}", num_remote_peers);
+    println!("{:?}", params);
+    println!();
+
+    for g in 6 .. 13 { // Iterate over size of network.
+        let l = 2 * g + 1;
+        for net_type in 0 .. net_types { // Iterate over type of network
+            for net_iter in 0 .. net_iters { // Three iterations for each type of network
+                print!("g={:2}; ",g);
+                match net_type {
+                    0 => print!("rand    ; "),
+                    1 => print!("2d      ; "),
+                    2 => print!("rand+2d ; "),
+                    3 => print!("planar  ; "),
+                    4 => print!("tree    ; "),
+                    _ => unreachable!(),
+                }
+                print!("ni={:1} |",net_iter);
+
+                /* Generate network */
+                let seed: &[_] = &[1,g,net_type,net_iter];
+                let mut network_rng: StdRng = rand::SeedableRng::from_seed(seed);
+                let net = gen_network(net_type, g, l, 0x10000, 0x20000, &mut network_rng);
+                let num_nodes = net.igraph.node_count();
+
+                let amount_close = ((num_nodes as f64).log(2.0) as usize).pow(2);
+                // Amount of landmarks can not be above half of the node count:
+                let mut num_landmarks: usize = g * g;
+                if num_landmarks as f64 > (num_nodes as f64) / 2.0 {
+                    num_landmarks = num_nodes / 2;
+                }
+                let landmarks = choose_landmarks(&net, num_landmarks, &mut network_rng);
+                let coords = match build_coords(&net, &landmarks) {
+                    Some(coords) => coords,
+                    None => unreachable!(),
+                };
+
+                let mut pair_rng: StdRng =
+                    rand::SeedableRng::from_seed(&[2,g, net_type, net_iter] as &[_]);
+                let node_pairs = (0 .. num_pairs)
+                    .map(|_| {
+                        let mut node_pair = choose_k_nums(2, num_nodes, &mut pair_rng)
+                            .into_iter().collect::<Vec<usize>>();
+                        // Sort for determinism:
+                        node_pair.sort();
+                        (node_pair[0], node_pair[1])
+                    })
+                    .collect::<Vec<(usize, usize)>>();
+
+                /* Converge Vivaldi coordinates */
+                let mut vivaldi_rng: StdRng =
+                    rand::SeedableRng::from_seed(&[3,g, net_type, net_iter] as &[_]);
+                let peers = choose_peers(&net, num_remote_peers, &mut vivaldi_rng);
+                let mut nodes = init_vivaldi(&net, params.dims);
+                let mut rounds_done = 0;
+                for &checkpoint in &checkpoints {
+                    let stats = converge_vivaldi(&peers, &mut nodes, &params,
+                                                 checkpoint - rounds_done, &mut vivaldi_rng);
+                    rounds_done = checkpoint;
+                    let last_stats = stats.last().unwrap();
+                    print!(" r{}: err = {:5.3}, mov = {:8.1} |",
+                           checkpoint, last_stats.avg_error, last_stats.avg_movement);
+                }
+
+                let mut rel_errors = vivaldi_rel_errors(&net, &nodes, &node_pairs);
+                rel_errors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                print!(" rel_err50 = {:5.3},", rel_errors[rel_errors.len() / 2]);
+                print!(" rel_err90 = {:5.3} |", rel_errors[rel_errors.len() * 9 / 10]);
+
+                /* Greedy routing */
+                let count_found = |node_dist: &dyn Fn(usize, usize) -> f64| {
+                    node_pairs.iter()
+                        .filter(|&&(src_node, dst_node)|
+                                find_path_greedy(src_node, dst_node, amount_close,
+                                                 &net, node_dist).is_some())
+                        .count()
+                };
+                let vivaldi_found = count_found(&|u, v| nodes[u].coord.dist(&nodes[v].coord));
+                let landmarks_found = count_found(&|u, v|
+                        approx_max_dist(u, v, &coords, &landmarks) as f64);
+                print!(" greedy_found: vivaldi = {:5.3}, landmarks = {:5.3}",
+                       (vivaldi_found as f64) / (num_pairs as f64),
+                       (landmarks_found as f64) / (num_pairs as f64));
+                println!();
+            }
+        }
+        println!();
+    }
+}
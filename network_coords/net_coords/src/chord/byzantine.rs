@@ -0,0 +1,387 @@
+extern crate rand;
+
+use std::collections::{HashSet, HashMap, VecDeque};
+use std::collections::hash_map::Entry;
+
+use self::rand::{Rng};
+
+use network::{Network};
+use random_util::{choose_k_nums};
+use index_pair::{index_pair, Pair};
+use chord::{RingKey, vdist, insert_neighbors_schains, create_semi_chains};
+use chord::node_fingers::{NodeFingers, SemiChain};
+use chord::semi_chains_array::{SemiChainsArray};
+use chord::ring_key::{RingKeyOps};
+
+/// The way byzantine nodes lie when sending updates to honest nodes.
+/// Byzantine nodes always update their own fingers honestly, and they are honest
+/// with each other.
+#[derive(Clone, Debug)]
+pub enum ByzantineBehavior<K = RingKey> {
+    /// Advertise all known chains as having length 0,
+    /// to attract chains through the byzantine node.
+    FakeShortChains,
+    /// Never send any update.
+    DropUpdates,
+    /// Advertise a perfect fake chain (final_id == target_id) for every finger
+    /// of the receiver with target_id inside the cyclic range [start, end).
+    Eclipse(K, K),
+}
+
+/// A set of byzantine nodes in a network, all behaving the same way.
+pub struct Adversary<K = RingKey> {
+    pub behavior: ByzantineBehavior<K>,
+    pub byzantine: HashSet<usize>,
+    // Fake ids advertised by byzantine nodes -> index of the byzantine node.
+    // Contacting a fake id ends up at the byzantine node that advertised it.
+    fake_owners: HashMap<K, usize>,
+}
+
+/// Metrics for the effect of byzantine nodes on honest nodes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ByzantineReport {
+    /// Total amount of fingers maintained by honest nodes.
+    pub honest_fingers: usize,
+    /// Honest fingers pointing to a fake id, or claiming a chain shorter than
+    /// the shortest path in the network.
+    pub poisoned_fingers: usize,
+    /// Honest fingers that do not reach the globally best key.
+    pub suboptimal_fingers: usize,
+    /// Amount of lookups performed between honest nodes.
+    pub lookups: usize,
+    /// Lookups that did not arrive at their destination.
+    pub failed_lookups: usize,
+}
+
+
+impl<K: RingKeyOps> Adversary<K> {
+    /// Randomly choose a fraction of the network nodes to be byzantine.
+    pub fn new<R: Rng>(net: &Network<K>, fraction: f64,
+                       behavior: ByzantineBehavior<K>, rng: &mut R) -> Adversary<K> {
+        assert!((0.0 .. 1.0).contains(&fraction), "Invalid byzantine fraction!");
+        let num_nodes = net.igraph.node_count();
+        let num_byzantine = ((num_nodes as f64) * fraction) as usize;
+        Adversary {
+            behavior,
+            byzantine: choose_k_nums(num_byzantine, num_nodes, rng),
+            fake_owners: HashMap::new(),
+        }
+    }
+
+    pub fn is_byzantine(&self, x_i: usize) -> bool {
+        self.byzantine.contains(&x_i)
+    }
+
+    /// Get the index of the node that answers when contacting id.
+    fn resolve(&self, net: &Network<K>, id: K) -> Option<usize> {
+        match net.node_to_index(&id) {
+            Some(index) => Some(index),
+            None => self.fake_owners.get(&id).cloned(),
+        }
+    }
+
+    /// Check if id is inside the eclipsed range.
+    fn is_eclipsed(&self, id: K, l: usize) -> bool {
+        match self.behavior {
+            ByzantineBehavior::Eclipse(start, end) =>
+                vdist(start, id, l) < vdist(start, end, l),
+            _ => false,
+        }
+    }
+
+    /// Create the semi chains a byzantine node advertises to the receiver.
+    fn forge(&mut self, net: &Network<K>, src_i: usize, src: &NodeFingers<K>,
+             dst: &NodeFingers<K>, l: usize) -> Vec<SemiChain<K>> {
+        match self.behavior {
+            ByzantineBehavior::FakeShortChains => {
+                src.all_schains().into_iter()
+                    .map(|schain| SemiChain {
+                        final_id: schain.final_id,
+                        length: 0,
+                    }).collect::<Vec<_>>()
+            },
+            ByzantineBehavior::DropUpdates => Vec::new(),
+            ByzantineBehavior::Eclipse(_, _) => {
+                let mut forged = Vec::new();
+                for finger in dst.all_fingers() {
+                    if !self.is_eclipsed(finger.target_id, l) {
+                        continue;
+                    }
+                    if net.node_to_index(&finger.target_id).is_none() {
+                        self.fake_owners.entry(finger.target_id).or_insert(src_i);
+                    }
+                    forged.push(SemiChain {
+                        final_id: finger.target_id,
+                        length: 0,
+                    });
+                }
+                forged
+            },
+        }
+    }
+
+    /// Send an update from src to dst, through a chain of length chain_length.
+    /// Returns true if any finger of dst has changed.
+    fn send_update(&mut self, net: &Network<K>, fingers: &mut [NodeFingers<K>],
+                   src_i: usize, dst_i: usize, chain_length: usize, l: usize) -> bool {
+
+        // Get two mutable indices (src_i and dst_i):
+        let (src, dst) = match index_pair(fingers, src_i, dst_i) {
+            Pair::Two(src, dst) => (src, dst),
+            _ => panic!("Invalid index pair: {}, {}", src_i, dst_i),
+        };
+
+        if !self.is_byzantine(src_i) || self.is_byzantine(dst_i) {
+            return dst.update_by_fingers(src, chain_length, l);
+        }
+        let forged = self.forge(net, src_i, src, dst, l);
+        dst.update_by_schains(&forged, chain_length, l)
+    }
+}
+
+/// Perform one iteration of fingers updates, where byzantine nodes lie.
+fn iter_fingers_byzantine<K: RingKeyOps>(net: &Network<K>, fingers: &mut [NodeFingers<K>],
+                          l: usize, adversary: &mut Adversary<K>) -> bool {
+
+    // Check if any finger has changed:
+    let mut has_changed = false;
+
+    for x_i in 0 .. net.igraph.node_count() {
+        for remote_schain in fingers[x_i].all_schains() {
+            let remote_i = adversary.resolve(net, remote_schain.final_id).unwrap();
+
+            if x_i == remote_i {
+                continue;
+            }
+
+            // UpdateRequest:
+            has_changed |= adversary.send_update(net, fingers, x_i, remote_i,
+                                                 remote_schain.length, l);
+            // UpdateResponse:
+            has_changed |= adversary.send_update(net, fingers, remote_i, x_i,
+                                                 remote_schain.length, l);
+        }
+    }
+
+    has_changed
+}
+
+/// Get to converging state of fingers for all the network,
+/// where the byzantine nodes of adversary lie in their updates.
+pub fn converge_fingers_byzantine<K: RingKeyOps>(net: &Network<K>,
+             fingers: &mut [NodeFingers<K>], l: usize, adversary: &mut Adversary<K>) {
+
+    // First iteration: We insert all edges:
+    insert_neighbors_schains(net, fingers, l);
+
+    while iter_fingers_byzantine(net, fingers, l, adversary) {}
+}
+
+/// Calculate hop distance from x_i to all other nodes in the network.
+fn hop_dists<K>(net: &Network<K>, x_i: usize) -> HashMap<usize, usize> {
+    let mut dists: HashMap<usize, usize> = HashMap::new();
+    let mut pending: VecDeque<usize> = VecDeque::new();
+    dists.insert(x_i, 0);
+    pending.push_back(x_i);
+    while let Some(cur_i) = pending.pop_front() {
+        let cur_dist = dists[&cur_i];
+        for nei_i in net.igraph.neighbors(cur_i) {
+            if let Entry::Vacant(entry) = dists.entry(nei_i) {
+                entry.insert(cur_dist + 1);
+                pending.push_back(nei_i);
+            }
+        }
+    }
+    dists
+}
+
+/// Find the best key in sorted_keys for a right finger (First key clockwise from target_id).
+fn best_right_key<K: RingKeyOps>(sorted_keys: &[K], target_id: K) -> K {
+    match sorted_keys.binary_search(&target_id) {
+        Ok(index) => sorted_keys[index],
+        Err(index) => sorted_keys[index % sorted_keys.len()],
+    }
+}
+
+/// Find the best key in sorted_keys for a left finger (First key counter-clockwise from target_id).
+fn best_left_key<K: RingKeyOps>(sorted_keys: &[K], target_id: K) -> K {
+    match sorted_keys.binary_search(&target_id) {
+        Ok(index) => sorted_keys[index],
+        Err(index) => sorted_keys[(index + sorted_keys.len() - 1) % sorted_keys.len()],
+    }
+}
+
+/// Route a lookup from src_id to dst_id between honest nodes.
+/// A lookup fails if it reaches a fake id or a byzantine node (Byzantine nodes drop all
+/// lookups), or if it does not make progress.
+/// Only the final nodes of semi chains are checked. Intermediate nodes along a chain
+/// are assumed to forward messages.
+fn lookup_byzantine<K: RingKeyOps>(src_id: K, dst_id: K, net: &Network<K>,
+                    semi_chains: &[SemiChainsArray<K>],
+                    adversary: &Adversary<K>) -> bool {
+
+    let mut cur_id = src_id;
+    // A bound on the amount of hops. Fake chains could make us go around in circles.
+    for _ in 0 .. net.igraph.node_count() {
+        if cur_id == dst_id {
+            return true;
+        }
+        let cur_i = match net.node_to_index(&cur_id) {
+            Some(cur_i) => cur_i,
+            None => return false, // Arrived at a fake id.
+        };
+        if adversary.is_byzantine(cur_i) {
+            return false;
+        }
+        let schain = semi_chains[cur_i].find_closest_left(dst_id);
+        if schain.final_id == cur_id {
+            return false;
+        }
+        cur_id = schain.final_id;
+    }
+    false
+}
+
+/// Measure how badly honest fingers were poisoned by the adversary, and how many lookups
+/// between random pairs of honest nodes fail.
+pub fn byzantine_report<K: RingKeyOps, R: Rng>(net: &Network<K>, fingers: &[NodeFingers<K>],
+                        adversary: &Adversary<K>, num_lookups: usize,
+                        rng: &mut R) -> ByzantineReport {
+
+    let mut sorted_keys: Vec<K> = (0 .. net.igraph.node_count())
+        .map(|x_i| *net.index_to_node(x_i).unwrap())
+        .collect::<Vec<_>>();
+    sorted_keys.sort();
+
+    let mut honest_fingers: usize = 0;
+    let mut poisoned_fingers: usize = 0;
+    let mut suboptimal_fingers: usize = 0;
+
+    let honest = (0 .. net.igraph.node_count())
+        .filter(|&x_i| !adversary.is_byzantine(x_i))
+        .collect::<Vec<usize>>();
+
+    for &x_i in &honest {
+        let dists = hop_dists(net, x_i);
+        let is_poisoned = |schain: &SemiChain<K>| {
+            match net.node_to_index(&schain.final_id) {
+                None => true,
+                Some(final_i) => schain.length < dists[&final_i],
+            }
+        };
+
+        for finger in &fingers[x_i].right.sorted_fingers {
+            honest_fingers += 1;
+            if is_poisoned(&finger.schain) {
+                poisoned_fingers += 1;
+            }
+            if finger.schain.final_id != best_right_key(&sorted_keys, finger.target_id) {
+                suboptimal_fingers += 1;
+            }
+        }
+        for finger in &fingers[x_i].left.sorted_fingers {
+            honest_fingers += 1;
+            if is_poisoned(&finger.schain) {
+                poisoned_fingers += 1;
+            }
+            if finger.schain.final_id != best_left_key(&sorted_keys, finger.target_id) {
+                suboptimal_fingers += 1;
+            }
+        }
+    }
+
+    let semi_chains = create_semi_chains(net, fingers);
+    let mut failed_lookups: usize = 0;
+    let mut lookups: usize = 0;
+    if honest.len() >= 2 {
+        for _ in 0 .. num_lookups {
+            let mut pair = choose_k_nums(2, honest.len(), rng).into_iter().collect::<Vec<_>>();
+            pair.sort(); // Make computation deterministic
+            let src_id = *net.index_to_node(honest[pair[0]]).unwrap();
+            let dst_id = *net.index_to_node(honest[pair[1]]).unwrap();
+            lookups += 1;
+            if !lookup_byzantine(src_id, dst_id, net, &semi_chains, adversary) {
+                failed_lookups += 1;
+            }
+        }
+    }
+
+    ByzantineReport {
+        honest_fingers,
+        poisoned_fingers,
+        suboptimal_fingers,
+        lookups,
+        failed_lookups,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use self::rand::{StdRng};
+    use network_gen::{gen_network};
+    use chord::{init_fingers, converge_fingers, verify_global_optimality};
+
+    #[test]
+    fn test_byzantine_no_adversary() {
+        let seed: &[_] = &[1,2,3,4,9];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let l: usize = 11;
+        let net = gen_network(0, 5, l, 1, 2, &mut rng);
+
+        let mut adversary = Adversary::new(&net, 0.0,
+                                ByzantineBehavior::FakeShortChains, &mut rng);
+        let mut fingers = init_fingers(&net, l, &mut rng);
+        converge_fingers_byzantine(&net, &mut fingers, l, &mut adversary);
+        assert!(verify_global_optimality(&net, &fingers));
+
+        let report = byzantine_report(&net, &fingers, &adversary, 100, &mut rng);
+        assert!(report.poisoned_fingers == 0);
+        assert!(report.suboptimal_fingers == 0);
+        assert!(report.lookups == 100);
+        assert!(report.failed_lookups == 0);
+    }
+
+    #[test]
+    fn test_byzantine_behaviors() {
+        let seed: &[_] = &[1,2,3,4,9];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let l: usize = 11;
+        let net = gen_network(0, 5, l, 1, 2, &mut rng);
+        let mut honest_fingers = init_fingers(&net, l, &mut rng);
+        converge_fingers(&net, &mut honest_fingers, l);
+
+        let behaviors = vec![
+            ByzantineBehavior::FakeShortChains,
+            ByzantineBehavior::DropUpdates,
+            ByzantineBehavior::Eclipse(0, 2_u64.pow(l as u32 - 2)),
+        ];
+
+        for behavior in behaviors {
+            let seed: &[_] = &[1,2,3,4,10];
+            let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+            let mut adversary = Adversary::new(&net, 0.2, behavior.clone(), &mut rng);
+            assert!(adversary.byzantine.len() == 6);
+            let mut fingers = init_fingers(&net, l, &mut rng);
+            converge_fingers_byzantine(&net, &mut fingers, l, &mut adversary);
+            let report = byzantine_report(&net, &fingers, &adversary, 100, &mut rng);
+            assert!(report.failed_lookups > 0);
+
+            match behavior {
+                ByzantineBehavior::DropUpdates => {
+                    // Dropping updates does not create any false information:
+                    assert!(report.poisoned_fingers == 0);
+                },
+                ByzantineBehavior::FakeShortChains => {
+                    // Only lengths are faked. All advertised ids are real:
+                    assert!(report.poisoned_fingers > 0);
+                },
+                ByzantineBehavior::Eclipse(_, _) => {
+                    assert!(report.poisoned_fingers > 0);
+                    assert!(report.suboptimal_fingers > 0);
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+/// Describes which target ids a node maintains right fingers for.
+/// Every field turns on one kind of target ids. The resulting set of target ids
+/// is the union of all enabled kinds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FingerStrategy {
+    /// Powers of 2 fingers: x + 2^i for every i in succ_pow_fingers.
+    pub succ_pow_fingers: Vec<usize>,
+    /// Powers of 2 fingers: x - 2^i for every i in pred_pow_fingers.
+    pub pred_pow_fingers: Vec<usize>,
+    /// Add all ids along ids_chain(x, neighbor) for every immediate neighbor.
+    pub neighbor_connectors: bool,
+    /// Add one random finger in [2^i, 2^(i+1)) for every i in [0, l).
+    pub rand_pow_fingers: bool,
+    /// Amount of fully random fingers in [0, 2^l).
+    pub num_rand_fingers: usize,
+}
+
+impl FingerStrategy {
+    /// The original mix of fingers:
+    /// ±2^i fingers, neighbor connectors, randomized fingers in [2^i, 2^(i+1))
+    /// and l fully random fingers.
+    pub fn full(l: usize) -> FingerStrategy {
+        FingerStrategy {
+            succ_pow_fingers: (0 .. l).collect::<Vec<usize>>(),
+            pred_pow_fingers: (0 .. l).collect::<Vec<usize>>(),
+            neighbor_connectors: true,
+            rand_pow_fingers: true,
+            num_rand_fingers: l,
+        }
+    }
+
+    /// Only the successor and the predecessor.
+    /// Matches SUCC_FINGERS = [0], PRED_FINGERS = [0] from custom_fingers.py
+    pub fn succ_pred() -> FingerStrategy {
+        FingerStrategy {
+            succ_pow_fingers: vec![0],
+            pred_pow_fingers: vec![0],
+            neighbor_connectors: false,
+            rand_pow_fingers: false,
+            num_rand_fingers: 0,
+        }
+    }
+
+    /// All ±2^i fingers, and nothing else.
+    /// Matches SUCC_FINGERS = PRED_FINGERS = range(IDENT_BITS) from notify_many_fingers.py
+    pub fn pow_fingers(l: usize) -> FingerStrategy {
+        FingerStrategy {
+            succ_pow_fingers: (0 .. l).collect::<Vec<usize>>(),
+            pred_pow_fingers: (0 .. l).collect::<Vec<usize>>(),
+            neighbor_connectors: false,
+            rand_pow_fingers: false,
+            num_rand_fingers: 0,
+        }
+    }
+
+    /// ±2^i fingers together with neighbor connectors.
+    /// No randomness is involved.
+    pub fn pow_fingers_connectors(l: usize) -> FingerStrategy {
+        FingerStrategy {
+            neighbor_connectors: true,
+            .. FingerStrategy::pow_fingers(l)
+        }
+    }
+
+    /// Successor and predecessor together with neighbor connectors.
+    pub fn succ_pred_connectors() -> FingerStrategy {
+        FingerStrategy {
+            neighbor_connectors: true,
+            .. FingerStrategy::succ_pred()
+        }
+    }
+
+    /// Get a list of all named presets, as (name, strategy) pairs.
+    pub fn presets(l: usize) -> Vec<(&'static str, FingerStrategy)> {
+        vec![
+            ("full", FingerStrategy::full(l)),
+            ("succ_pred", FingerStrategy::succ_pred()),
+            ("succ_pred_conn", FingerStrategy::succ_pred_connectors()),
+            ("pow", FingerStrategy::pow_fingers(l)),
+            ("pow_conn", FingerStrategy::pow_fingers_connectors(l)),
+        ]
+    }
+}
@@ -1,34 +1,26 @@
 use chord::{RingKey};
+use chord::ring_key::{RingKeyOps};
 
-pub struct IdsChain {
-    cur_id: Option<RingKey>, // Current id
-    dst_id: RingKey, // Destination id
+pub struct IdsChain<K = RingKey> {
+    cur_id: Option<K>, // Current id
+    dst_id: K, // Destination id
 }
 
 /// Find the msb bit index of a given number.
-fn get_msb(mut x: RingKey) -> Option<usize> {
-    match x {
-        0 => None,
-        _ => {
-            let mut index: usize = 0;
-            while x > 0 {
-                x >>= 1;
-                index += 1;
-            }
-            Some(index - 1)
-        }
-    }
+fn get_msb<K: RingKeyOps>(x: K) -> Option<usize> {
+    x.msb()
 }
 
-fn advance_id(cur_id: RingKey, dst_id: RingKey) -> RingKey {
+fn advance_id<K: RingKeyOps>(cur_id: K, dst_id: K) -> K {
     // Find the most significant different bit between cur_id and dst_id:
-    let msb_diff: usize = get_msb(cur_id ^ dst_id).unwrap();
+    let msb_diff: usize = get_msb(cur_id.bitxor(dst_id)).unwrap();
 
     // Check if we need to add or to subtract:
-    let pow_diff: RingKey = 2_u64.pow(msb_diff as u32);
-    match (cur_id >> msb_diff) & 1 {
-        0 => cur_id + pow_diff,
-        _ => cur_id - pow_diff,
+    let pow_diff: K = K::pow2(msb_diff);
+    if cur_id.bit(msb_diff) {
+        cur_id.wrapping_sub(pow_diff)
+    } else {
+        cur_id.wrapping_add(pow_diff)
     }
 }
 
@@ -38,9 +30,9 @@ fn advance_id(cur_id: RingKey, dst_id: RingKey) -> RingKey {
 /// power of 2.
 /// This iterator is guaranteed to be deterministic. (It will return the same
 /// chain for the same source and destination ids every time).
-impl Iterator for IdsChain {
-    type Item = RingKey;
-    fn next(&mut self) -> Option<RingKey> {
+impl<K: RingKeyOps> Iterator for IdsChain<K> {
+    type Item = K;
+    fn next(&mut self) -> Option<K> {
         match self.cur_id {
             None => None,
             Some(cur_id) => {
@@ -55,7 +47,7 @@ impl Iterator for IdsChain {
     }
 }
 
-pub fn ids_chain(src_id: RingKey, dst_id: RingKey) -> IdsChain {
+pub fn ids_chain<K: RingKeyOps>(src_id: K, dst_id: K) -> IdsChain<K> {
     IdsChain {
         cur_id: Some(src_id),
         dst_id: dst_id,
@@ -68,21 +60,21 @@ mod tests {
 
     #[test]
     fn test_get_msb() {
-        assert!(get_msb(0) == None);
-        assert!(get_msb(1) == Some(0));
-        assert!(get_msb(2) == Some(1));
-        assert!(get_msb(3) == Some(1));
-        assert!(get_msb(4) == Some(2));
-        assert!(get_msb(5) == Some(2));
-        assert!(get_msb(6) == Some(2));
-        assert!(get_msb(7) == Some(2));
-        assert!(get_msb(8) == Some(3));
-        assert!(get_msb(9) == Some(3));
+        assert!(get_msb(0u64) == None);
+        assert!(get_msb(1u64) == Some(0));
+        assert!(get_msb(2u64) == Some(1));
+        assert!(get_msb(3u64) == Some(1));
+        assert!(get_msb(4u64) == Some(2));
+        assert!(get_msb(5u64) == Some(2));
+        assert!(get_msb(6u64) == Some(2));
+        assert!(get_msb(7u64) == Some(2));
+        assert!(get_msb(8u64) == Some(3));
+        assert!(get_msb(9u64) == Some(3));
     }
 
     #[test]
     fn test_ids_chain_trivial() {
-        let ic = ids_chain(0,1).collect::<Vec<_>>();
+        let ic = ids_chain(0u64,1).collect::<Vec<_>>();
         println!("{:?}",ic);
         assert!(ic[0] == 0);
         assert!(ic[1] == 1);
@@ -107,11 +99,27 @@ mod tests {
 
     #[test]
     fn test_ids_chain_long() {
-        let ic = ids_chain(0xdeadbeef,0x12345678).collect::<Vec<_>>();
+        let ic = ids_chain(0xdeadbeef_u64,0x12345678).collect::<Vec<_>>();
         let ic0 = ic.iter();
         let ic1 = ic.iter().skip(1);
 
         assert!(ic1.zip(ic0).all(|(&next_id, &id)| is_adjacent(next_id, id)));
         assert!(ic.len() > 2);
     }
+
+    #[test]
+    fn test_ids_chain_wide() {
+        use chord::ring_key::{U256};
+        let src_id = U256::new(0xdeadbeef, 0x1234);
+        let dst_id = U256::new(0x12345678, 0xffff_0000_0000_0000_0000);
+        let ic = ids_chain(src_id, dst_id).collect::<Vec<_>>();
+
+        // Every two adjacent ids differ by an exact power of 2:
+        for i in 0 .. ic.len() - 1 {
+            let (low, high) = if ic[i] < ic[i+1] {(ic[i], ic[i+1])} else {(ic[i+1], ic[i])};
+            let diff = high.wrapping_sub(low);
+            assert!(diff == U256::pow2(diff.msb().unwrap()));
+        }
+        assert!(ic[ic.len() - 1] == dst_id);
+    }
 }
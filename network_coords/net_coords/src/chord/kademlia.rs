@@ -0,0 +1,249 @@
+use std::collections::{HashMap};
+
+use network::{Network};
+use chord::{RingKey};
+use chord::node_fingers::{SemiChain};
+use chord::ring_key::{RingKeyOps};
+use index_pair::{index_pair, Pair};
+
+/// XOR distance between two keys.
+pub fn xor_dist<K: RingKeyOps>(xk: K, yk: K) -> K {
+    xk.bitxor(yk)
+}
+
+/// Kademlia style k-buckets of a node.
+/// Bucket i contains contacts y where the most significant bit of (x xor y) is i.
+/// Every contact is a semi chain: A path discovered in the network, of a known length.
+/// Every bucket keeps at most k contacts, preferring shorter chains.
+pub struct KBuckets<K = RingKey> {
+    id: K,
+    k: usize,
+    /// Contacts in every bucket, sorted by (length, final_id).
+    pub buckets: Vec<Vec<SemiChain<K>>>,
+    version: usize, // Current version, used for caching.
+    updated_by: HashMap<K, usize>,
+}
+
+impl<K: RingKeyOps> KBuckets<K> {
+    pub fn new(x_id: K, k: usize, l: usize) -> KBuckets<K> {
+        assert!(k > 0, "Buckets must have room for at least one contact!");
+        KBuckets {
+            id: x_id,
+            k,
+            buckets: (0 .. l).map(|_| Vec::new()).collect::<Vec<_>>(),
+            version: 1,
+            updated_by: HashMap::new(),
+        }
+    }
+
+    /// Add a new known chain, possibly replacing a contact with a longer chain.
+    /// Returns true if any bucket has changed.
+    pub fn update(&mut self, schain: &SemiChain<K>) -> bool {
+        let bucket_index = match xor_dist(self.id, schain.final_id).msb() {
+            Some(bucket_index) => bucket_index,
+            None => return false, // This is our own id.
+        };
+        let k = self.k;
+        let bucket = &mut self.buckets[bucket_index];
+
+        if let Some(pos) = bucket.iter().position(|c| c.final_id == schain.final_id) {
+            if bucket[pos].length <= schain.length {
+                return false;
+            }
+            // Found a shorter chain to a known contact:
+            bucket.remove(pos);
+        } else if bucket.len() >= k {
+            // Bucket is full. Replace the worst contact only if the new one is better:
+            let worst = &bucket[bucket.len() - 1];
+            if (worst.length, worst.final_id) <= (schain.length, schain.final_id) {
+                return false;
+            }
+            bucket.pop();
+        }
+
+        let insert_index = match bucket.binary_search_by_key(
+            &(schain.length, schain.final_id), |c| (c.length, c.final_id)) {
+            Ok(index) | Err(index) => index,
+        };
+        bucket.insert(insert_index, schain.clone());
+        self.version += 1;
+        true
+    }
+
+    /// Get all contacts in all buckets.
+    pub fn all_contacts(&self) -> Vec<SemiChain<K>> {
+        self.buckets.iter()
+            .flat_map(|bucket| bucket.iter().cloned())
+            .collect::<Vec<_>>()
+    }
+
+    /// Update buckets by all contacts of buckets_src,
+    /// assuming that there is a connecting chain between the two
+    /// of length chain_length.
+    /// Return if any bucket in self has changed.
+    pub fn update_by_buckets(&mut self, buckets_src: &KBuckets<K>,
+                             chain_length: usize) -> bool {
+
+        // 0 is a reserved version number, which means we know nothing of buckets_src.
+        let last_version = match self.updated_by.get(&buckets_src.id) {
+            Some(&last_version) => last_version,
+            None => 0,
+        };
+        if last_version == buckets_src.version {
+            // We are already updated about this version of buckets_src.
+            return false;
+        }
+
+        let mut has_changed = self.update(&SemiChain {
+            final_id: buckets_src.id,
+            length: chain_length,
+        });
+        for schain in buckets_src.all_contacts() {
+            has_changed |= self.update(&SemiChain {
+                final_id: schain.final_id,
+                length: schain.length + chain_length,
+            });
+        }
+
+        self.updated_by.insert(buckets_src.id, buckets_src.version);
+        has_changed
+    }
+
+    /// Find the contact closest to dst_id by XOR distance.
+    /// Among contacts of the same distance, the shortest chain is chosen.
+    pub fn find_closest(&self, dst_id: K) -> Option<&SemiChain<K>> {
+        self.buckets.iter()
+            .flat_map(|bucket| bucket.iter())
+            .min_by_key(|c| (xor_dist(c.final_id, dst_id), c.length))
+    }
+}
+
+/// Create empty k-buckets for all nodes in the network.
+pub fn init_kbuckets<K: RingKeyOps>(net: &Network<K>, k: usize, l: usize) -> Vec<KBuckets<K>> {
+    assert!(l <= K::num_bits(), "Key space is too large for the key type!");
+    (0 .. net.igraph.node_count())
+        .map(|x_i| KBuckets::new(*net.index_to_node(x_i).unwrap(), k, l))
+        .collect::<Vec<_>>()
+}
+
+/// Perform one iteration of buckets updates. Every node exchanges
+/// buckets with all of its contacts.
+fn iter_kbuckets<K: RingKeyOps>(net: &Network<K>, kbuckets: &mut [KBuckets<K>]) -> bool {
+    // Check if any bucket has changed:
+    let mut has_changed = false;
+
+    for x_i in 0 .. net.igraph.node_count() {
+        for remote_schain in kbuckets[x_i].all_contacts() {
+            let remote_i = net.node_to_index(&remote_schain.final_id).unwrap();
+
+            // Get two mutable indices (x_i and remote_i):
+            let (m_x_i, m_remote_i) = match index_pair(kbuckets, x_i, remote_i) {
+                Pair::Two(m_x_i,m_remote_i) => (m_x_i, m_remote_i),
+                _ => panic!("Invalid index pair: {}, {}", x_i, remote_i),
+            };
+
+            // UpdateRequest:
+            has_changed |= m_remote_i.update_by_buckets(m_x_i, remote_schain.length);
+            // UpdateResponse:
+            has_changed |= m_x_i.update_by_buckets(m_remote_i, remote_schain.length);
+        }
+    }
+    has_changed
+}
+
+/// Get to converging state of k-buckets for all the network.
+pub fn converge_kbuckets<K: RingKeyOps>(net: &Network<K>, kbuckets: &mut [KBuckets<K>]) {
+    // First iteration: We insert all edges:
+    for (x_i, x_kbuckets) in kbuckets.iter_mut().enumerate() {
+        let mut neighbors = net.igraph.neighbors(x_i).collect::<Vec<_>>();
+        neighbors.sort();
+        for neighbor_i in neighbors {
+            let schain = SemiChain {
+                final_id: *net.index_to_node(neighbor_i).unwrap(),
+                length: 1,
+            };
+            x_kbuckets.update(&schain);
+        }
+    }
+
+    while iter_kbuckets(net, kbuckets) {}
+}
+
+/// Returns a length of a found path between src_id to dst_id, or
+/// None if no path was found.
+/// Greedy routing by XOR distance: At every hop we move to the contact
+/// closest to dst_id. XOR distance strictly decreases at every hop, hence this function
+/// always terminates.
+pub fn find_path_xor<K: RingKeyOps>(src_id: K, dst_id: K, net: &Network<K>,
+                 kbuckets: &[KBuckets<K>]) -> Option<usize> {
+
+    let mut cur_id = src_id;
+    let mut length: usize = 0;
+    while cur_id != dst_id {
+        let cur_kbuckets = &kbuckets[net.node_to_index(&cur_id).unwrap()];
+        let schain = cur_kbuckets.find_closest(dst_id)?;
+        if xor_dist(schain.final_id, dst_id) >= xor_dist(cur_id, dst_id) {
+            return None;
+        }
+
+        length += schain.length;
+        cur_id = schain.final_id;
+    }
+    Some(length)
+}
+
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+
+    use super::*;
+    use self::rand::{StdRng};
+    use network_gen::{gen_network};
+
+    #[test]
+    fn test_kbuckets_update() {
+        let l = 4;
+        let mut kb = KBuckets::new(0b0000_u64, 2, l);
+        // Own id is ignored:
+        assert!(!kb.update(&SemiChain {final_id: 0b0000, length: 0}));
+
+        assert!(kb.update(&SemiChain {final_id: 0b1000, length: 3}));
+        assert!(kb.update(&SemiChain {final_id: 0b1001, length: 2}));
+        // Bucket is full, and the new contact is worse:
+        assert!(!kb.update(&SemiChain {final_id: 0b1010, length: 5}));
+        // Better than the worst contact:
+        assert!(kb.update(&SemiChain {final_id: 0b1011, length: 1}));
+        assert!(kb.buckets[3] == vec![SemiChain {final_id: 0b1011, length: 1},
+                                      SemiChain {final_id: 0b1001, length: 2}]);
+        // Shorter chain to a known contact:
+        assert!(kb.update(&SemiChain {final_id: 0b1001, length: 1}));
+        assert!(!kb.update(&SemiChain {final_id: 0b1001, length: 1}));
+
+        assert!(kb.update(&SemiChain {final_id: 0b0001, length: 4}));
+        assert!(kb.buckets[0] == vec![SemiChain {final_id: 0b0001, length: 4}]);
+        assert!(kb.find_closest(0b0011).unwrap().final_id == 0b0001);
+        assert!(kb.find_closest(0b1010).unwrap().final_id == 0b1011);
+    }
+
+    #[test]
+    fn test_find_path_xor() {
+        for net_type in 0 .. 5 {
+            let seed: &[_] = &[1,2,3,4,9,net_type];
+            let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+            let l: usize = 11;
+            let net = gen_network(net_type, 5, l, 1, 2, &mut rng);
+
+            let mut kbuckets = init_kbuckets(&net, 4, l);
+            converge_kbuckets(&net, &mut kbuckets);
+
+            for src_i in 0 .. net.igraph.node_count() {
+                for dst_i in 0 .. net.igraph.node_count() {
+                    let src_id = *net.index_to_node(src_i).unwrap();
+                    let dst_id = *net.index_to_node(dst_i).unwrap();
+                    assert!(find_path_xor(src_id, dst_id, &net, &kbuckets).is_some());
+                }
+            }
+        }
+    }
+}
@@ -5,16 +5,21 @@ extern crate rand;
 pub mod ids_chain;
 pub mod semi_chains_array;
 pub mod node_fingers;
+pub mod finger_strategy;
+pub mod ring_key;
+pub mod byzantine;
+pub mod kademlia;
 
 use std::collections::{HashSet};
 
 use self::rand::{Rng};
-use self::rand::distributions::{IndependentSample, Range};
 
 use network::{Network};
 use self::ids_chain::{ids_chain};
 use self::semi_chains_array::{SemiChainsArray};
 use self::node_fingers::{NodeFingers, SemiChain};
+use self::finger_strategy::{FingerStrategy};
+use self::ring_key::{RingKeyOps};
 use index_pair::{index_pair, Pair};
 
 
@@ -35,91 +40,119 @@ pub type NeighborConnector = Vec<NodeChain>;
 
 
 /// Calculate ring distance from x to y clockwise
-fn vdist(xk:RingKey, yk: RingKey, l: usize) -> RingKey {
-    (yk.wrapping_sub(xk)) % 2_u64.pow(l as u32)
+fn vdist<K: RingKeyOps>(xk: K, yk: K, l: usize) -> K {
+    yk.wrapping_sub(xk).mod_pow2(l)
 }
 
-/// Add cyclic (x + diff) % max_key
-fn add_cyc(x: RingKey, diff: i64, l: usize) -> RingKey {
-    let max_key = 2_u64.pow(l as u32);
-    (if diff >= 0 {
-        x.wrapping_add(diff as u64)
+/// Calculate ring distance between x and y, going in the shorter direction
+/// (Either clockwise or counter-clockwise).
+pub fn ring_dist<K: RingKeyOps>(xk: K, yk: K, l: usize) -> K {
+    let cw_dist = vdist(xk, yk, l);
+    let ccw_dist = vdist(yk, xk, l);
+    if cw_dist < ccw_dist {
+        cw_dist
     } else {
-        x.wrapping_sub((-diff) as u64)
-    }) % max_key
+        ccw_dist
+    }
+}
+
+/// Add cyclic (x + diff) % max_key
+fn add_cyc<K: RingKeyOps>(x: K, diff: K, l: usize) -> K {
+    x.wrapping_add(diff).mod_pow2(l)
+}
+
+/// Subtract cyclic (x - diff) % max_key
+fn sub_cyc<K: RingKeyOps>(x: K, diff: K, l: usize) -> K {
+    x.wrapping_sub(diff).mod_pow2(l)
 }
 
 
 /// Generate a vector of maintained left target_ids for node with id x_id.
-fn gen_left_target_ids(x_id: RingKey, l: usize) -> Vec<RingKey> {
-    vec![add_cyc(x_id,-1,l)]
+fn gen_left_target_ids<K: RingKeyOps>(x_id: K, l: usize) -> Vec<K> {
+    vec![sub_cyc(x_id, K::pow2(0), l)]
 }
 
 /// Generate a vector of maintained right target_ids for node with id x_id.
-fn gen_right_target_ids<R: Rng>(x_id: RingKey, net: &Network<RingKey>, 
-                                l: usize, mut rng: &mut R) -> Vec<RingKey> {
+/// The kinds of target ids are chosen according to finger_strategy.
+fn gen_right_target_ids<K: RingKeyOps, R: Rng>(x_id: K, net: &Network<K>, 
+                                l: usize, finger_strategy: &FingerStrategy,
+                                mut rng: &mut R) -> Vec<K> {
 
-    let mut target_ids_set: HashSet<RingKey> = HashSet::new();
+    let mut target_ids_set: HashSet<K> = HashSet::new();
     // let mut target_ids: Vec<RingKey> = Vec::new();
 
     // Basic right fingers:
-    for i in 0 .. l {
-        let pow_val = 2_i64.pow(i as u32);
-        target_ids_set.insert(add_cyc(x_id,pow_val,l));
-        target_ids_set.insert(add_cyc(x_id,-pow_val,l));
+    for &i in &finger_strategy.succ_pow_fingers {
+        target_ids_set.insert(add_cyc(x_id,K::pow2(i),l));
+    }
+    for &i in &finger_strategy.pred_pow_fingers {
+        target_ids_set.insert(sub_cyc(x_id,K::pow2(i),l));
     }
 
     // Neighbor connectors:
-    let x_i = net.node_to_index(&x_id).unwrap();
-    let mut neighbors = net.igraph.neighbors(x_i).into_iter().collect::<Vec<_>>();
-    neighbors.sort();
-    for neighbor_index in neighbors {
-        let neighbor_id: RingKey = net.index_to_node(neighbor_index).unwrap().clone();
-        for cur_id in ids_chain(x_id, neighbor_id) {
-            target_ids_set.insert(cur_id);
+    if finger_strategy.neighbor_connectors {
+        let x_i = net.node_to_index(&x_id).unwrap();
+        let mut neighbors = net.igraph.neighbors(x_i).collect::<Vec<_>>();
+        neighbors.sort();
+        for neighbor_index in neighbors {
+            let neighbor_id: K = *net.index_to_node(neighbor_index).unwrap();
+            for cur_id in ids_chain(x_id, neighbor_id) {
+                target_ids_set.insert(cur_id);
+            }
         }
     }
 
     // Right randomized fingers:
-    for i in 0 .. l {
-        // Randomize a finger value in [2^i, 2^(i+1))
-        let rand_range: Range<RingKey> = 
-            Range::new(2_u64.pow(i as u32),2_u64.pow((i + 1) as u32));
-        let rand_id = rand_range.ind_sample(&mut rng);
-        target_ids_set.insert(rand_id);
+    if finger_strategy.rand_pow_fingers {
+        for i in 0 .. l {
+            // Randomize a finger value in [2^i, 2^(i+1))
+            let rand_id = K::rand_pow2_interval(i, &mut rng).mod_pow2(l);
+            target_ids_set.insert(rand_id);
+        }
     }
 
     // Update random fingers:
-    for _ in 0 .. l {
+    for _ in 0 .. finger_strategy.num_rand_fingers {
         // Randomize a finger value in [0, 2^l). Completely random in the ring key space.
-        let rand_range: Range<RingKey> = Range::new(0u64,2_u64.pow(l as u32));
-        let rand_id = rand_range.ind_sample(&mut rng);
+        let rand_id = K::rand_key(l, &mut rng);
         target_ids_set.insert(rand_id);
     }
     
-    target_ids_set.into_iter().collect::<Vec<RingKey>>()
+    target_ids_set.into_iter().collect::<Vec<K>>()
 
 }
 
 /// Initialize maintained fingers for node with index x_i.
-fn create_node_fingers<R: Rng>(x_i: usize, net: &Network<RingKey>, 
-             l: usize, mut rng: &mut R) -> NodeFingers {
+fn create_node_fingers<K: RingKeyOps, R: Rng>(x_i: usize, net: &Network<K>, 
+             l: usize, finger_strategy: &FingerStrategy, mut rng: &mut R) -> NodeFingers<K> {
 
     let x_id = net.index_to_node(x_i).unwrap().clone();
     let target_ids_left = gen_left_target_ids(x_id, l);
-    let target_ids_right = gen_right_target_ids(x_id, &net, l, &mut rng);
+    let target_ids_right = gen_right_target_ids(x_id, net, l, 
+                                                finger_strategy, &mut rng);
 
     let nf = NodeFingers::new(x_id, &target_ids_left, &target_ids_right);
     
     nf
 }
 
-pub fn init_fingers<R: Rng>(net: &Network<RingKey>, 
-                l: usize, mut rng: &mut R) -> Vec<NodeFingers> {
+pub fn init_fingers<K: RingKeyOps, R: Rng>(net: &Network<K>, 
+                l: usize, mut rng: &mut R) -> Vec<NodeFingers<K>> {
+
+    init_fingers_by_strategy(net, l, &FingerStrategy::full(l), &mut rng)
+}
+
+/// Initialize maintained fingers for all nodes, 
+/// choosing target ids according to finger_strategy.
+pub fn init_fingers_by_strategy<K: RingKeyOps, R: Rng>(net: &Network<K>, 
+                l: usize, finger_strategy: &FingerStrategy, 
+                mut rng: &mut R) -> Vec<NodeFingers<K>> {
+
+    assert!(l <= K::num_bits(), "Key space is too large for the key type!");
 
     let mut res_fingers = Vec::new();
     for x_i in 0 .. net.igraph.node_count() {
-        res_fingers.push(create_node_fingers(x_i, &net, l, &mut rng));
+        res_fingers.push(create_node_fingers(x_i, net, l, finger_strategy, &mut rng));
     }
 
     res_fingers
@@ -128,8 +161,8 @@ pub fn init_fingers<R: Rng>(net: &Network<RingKey>,
 
 
 /// Perform one iteration of fingers for all nodes
-fn iter_fingers(net: &Network<RingKey>, 
-                mut fingers: &mut Vec<NodeFingers>, l: usize) -> bool {
+fn iter_fingers<K: RingKeyOps>(net: &Network<K>, 
+                mut fingers: &mut Vec<NodeFingers<K>>, l: usize) -> bool {
 
     // Check if any finger has changed:
     let mut has_changed = false;
@@ -170,11 +203,10 @@ fn iter_fingers(net: &Network<RingKey>,
 }
 
 
-/// Get to converging state of fingers for all the network.
-pub fn converge_fingers(net: &Network<RingKey>, 
-             mut fingers: &mut Vec<NodeFingers>, l: usize) {
+/// First iteration of fingers convergence: Every node learns about its immediate neighbors.
+fn insert_neighbors_schains<K: RingKeyOps>(net: &Network<K>, 
+             fingers: &mut [NodeFingers<K>], l: usize) {
 
-    // First iteration: We insert all edges:
     for x_i in 0 .. net.igraph.node_count() {
         let mut neighbors = net.igraph.neighbors(x_i).into_iter().collect::<Vec<_>>();
         neighbors.sort();
@@ -187,6 +219,14 @@ pub fn converge_fingers(net: &Network<RingKey>,
             fingers[x_i].update(&schain,l);
         }
     }
+}
+
+/// Get to converging state of fingers for all the network.
+pub fn converge_fingers<K: RingKeyOps>(net: &Network<K>, 
+             mut fingers: &mut Vec<NodeFingers<K>>, l: usize) {
+
+    // First iteration: We insert all edges:
+    insert_neighbors_schains(net, fingers, l);
 
     // println!("Iterating fingers...");
     // print!(".");
@@ -198,9 +238,10 @@ pub fn converge_fingers(net: &Network<RingKey>,
 
 /// Make sure that every finger reaches the best globally key possible
 /// (As closest as possible to its target_id).
-pub fn verify_global_optimality(net: &Network<RingKey>, fingers: &Vec<NodeFingers>) -> bool {
+pub fn verify_global_optimality<K: RingKeyOps>(net: &Network<K>, 
+                                               fingers: &Vec<NodeFingers<K>>) -> bool {
     // Obtain a sorted vector of all keys in the network:
-    let mut all_keys: Vec<RingKey> = (0 .. net.igraph.node_count())
+    let mut all_keys: Vec<K> = (0 .. net.igraph.node_count())
         .map(|x_i| net.index_to_node(x_i).unwrap().clone())
         .collect::<Vec<_>>();
     all_keys.sort();
@@ -215,8 +256,8 @@ pub fn verify_global_optimality(net: &Network<RingKey>, fingers: &Vec<NodeFinger
 
 
 
-fn create_semi_chains_node(x_i: usize, 
-                           fingers: &Vec<NodeFingers>) -> SemiChainsArray {
+fn create_semi_chains_node<K: RingKeyOps>(x_i: usize, 
+                           fingers: &[NodeFingers<K>]) -> SemiChainsArray<K> {
 
     let mut schains_array = SemiChainsArray::new();
 
@@ -228,8 +269,8 @@ fn create_semi_chains_node(x_i: usize,
     schains_array
 }
 
-pub fn create_semi_chains(net: &Network<RingKey>, 
-                          fingers: &Vec<NodeFingers>) -> Vec<SemiChainsArray> {
+pub fn create_semi_chains<K: RingKeyOps>(net: &Network<K>, 
+                          fingers: &[NodeFingers<K>]) -> Vec<SemiChainsArray<K>> {
 
     let mut res_vec = Vec::new();
     for x_i in 0 .. net.igraph.node_count() {
@@ -240,8 +281,8 @@ pub fn create_semi_chains(net: &Network<RingKey>,
 
 /// Returns a length of a found path between src_id to dst_id, or 
 /// None if no path was found.
-pub fn find_path(src_id: RingKey, dst_id: RingKey, net: &Network<RingKey>, 
-                 semi_chains: &Vec<SemiChainsArray>) -> Option<usize> {
+pub fn find_path<K: RingKeyOps>(src_id: K, dst_id: K, net: &Network<K>, 
+                 semi_chains: &Vec<SemiChainsArray<K>>) -> Option<usize> {
 
     let mut cur_id = src_id;
     let mut length: usize = 0;
@@ -258,11 +299,57 @@ pub fn find_path(src_id: RingKey, dst_id: RingKey, net: &Network<RingKey>,
     Some(length)
 }
 
+/// Choose the next hop from cur_id towards dst_id, going in either direction of the ring.
+/// We consider both the closest known chain to the left of dst_id and the closest known
+/// chain to the right of dst_id, and pick the one that gets us closer to dst_id on the ring.
+fn next_hop_bidir<K: RingKeyOps>(dst_id: K, cur_semi_chains: &SemiChainsArray<K>, 
+                  l: usize) -> &SemiChain<K> {
+
+    let schain_left = cur_semi_chains.find_closest_left(dst_id);
+    let schain_right = cur_semi_chains.find_closest_right(dst_id);
+
+    if (ring_dist(schain_right.final_id, dst_id, l), schain_right.length) <
+        (ring_dist(schain_left.final_id, dst_id, l), schain_left.length) {
+        schain_right
+    } else {
+        schain_left
+    }
+}
+
+/// Returns a length of a found path between src_id to dst_id, or 
+/// None if no path was found.
+/// At every hop we move in the ring direction (clockwise or counter-clockwise) 
+/// that makes more progress towards dst_id.
+///
+/// Ring distance to dst_id strictly decreases at every hop, hence this function
+/// always terminates. If fingers are globally optimal, every node knows its successor
+/// and its predecessor, so a hop that strictly decreases the ring distance always exists.
+pub fn find_path_bidir<K: RingKeyOps>(src_id: K, dst_id: K, net: &Network<K>, 
+                 semi_chains: &[SemiChainsArray<K>], l: usize) -> Option<usize> {
+
+    let mut cur_id = src_id;
+    let mut length: usize = 0;
+    while cur_id != dst_id {
+        let cur_semi_chains = &semi_chains[net.node_to_index(&cur_id).unwrap()];
+        let schain = next_hop_bidir(dst_id, cur_semi_chains, l);
+        if ring_dist(schain.final_id, dst_id, l) >= ring_dist(cur_id, dst_id, l) {
+            // No progress is possible:
+            return None;
+        }
+
+        length += schain.length;
+        cur_id = schain.final_id;
+    }
+    Some(length)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use self::rand::{StdRng};
-    use network_gen::random_net_chord;
+    use network_gen::{random_net_chord, gen_network, rekey_network};
+    use chord::ring_key::{U256};
+    use chord::finger_strategy::{FingerStrategy};
 
     #[test]
     fn test_d() {
@@ -271,22 +358,42 @@ mod tests {
         assert!(vdist(1u64,101,l) == 100);
         assert!(vdist(2_u64.pow(l as u32) - 1,1,l) == 2);
         assert!(vdist(2_u64.pow(l as u32) - 1,0,l) == 1);
-        assert!(vdist(1,0,l) == 2_u64.pow(l as u32) - 1);
+        assert!(vdist(1u64,0,l) == 2_u64.pow(l as u32) - 1);
+
+        // Full width keys:
+        assert!(vdist(1u64,0,64) == u64::MAX);
+        assert!(vdist(u64::MAX,1,64) == 2);
+    }
+
+    #[test]
+    fn test_ring_dist() {
+        let l = 5;
+        assert!(ring_dist(1u64,2,l) == 1);
+        assert!(ring_dist(2u64,1,l) == 1);
+        assert!(ring_dist(0u64,31,l) == 1);
+        assert!(ring_dist(31u64,0,l) == 1);
+        assert!(ring_dist(3u64,3,l) == 0);
+        assert!(ring_dist(0u64,16,l) == 16);
+        assert!(ring_dist(2u64,20,l) == 14);
     }
 
     #[test]
     fn test_add_cyc() {
         // Check add:
-        assert!(add_cyc(0,1,5) == 1);
-        assert!(add_cyc(1,1,5) == 2);
-        assert!(add_cyc(30,1,5) == 31);
-        assert!(add_cyc(31,1,5) == 0);
+        assert!(add_cyc(0u64,1,5) == 1);
+        assert!(add_cyc(1u64,1,5) == 2);
+        assert!(add_cyc(30u64,1,5) == 31);
+        assert!(add_cyc(31u64,1,5) == 0);
 
         // Check sub:
-        assert!(add_cyc(2,-1,5) == 1);
-        assert!(add_cyc(1,-1,5) == 0);
-        assert!(add_cyc(0,-1,5) == 31);
-        assert!(add_cyc(31,-1,30) == 30);
+        assert!(sub_cyc(2u64,1,5) == 1);
+        assert!(sub_cyc(1u64,1,5) == 0);
+        assert!(sub_cyc(0u64,1,5) == 31);
+        assert!(sub_cyc(31u64,1,30) == 30);
+
+        // Check full width keys:
+        assert!(add_cyc(u64::MAX,1,64) == 0);
+        assert!(sub_cyc(0u64,1,64) == u64::MAX);
     }
 
 
@@ -338,4 +445,95 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_chord_finger_strategies() {
+        let seed: &[_] = &[1,2,3,4,9];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let num_nodes = 30;
+        let num_neighbors = 3;
+        let l: usize = 12; // Size of keyspace
+        let net = random_net_chord(num_nodes,num_neighbors,l,&mut rng);
+
+        for (_, finger_strategy) in FingerStrategy::presets(l) {
+            let mut fingers = init_fingers_by_strategy(&net, l, 
+                                               &finger_strategy, &mut rng);
+            converge_fingers(&net, &mut fingers,l);
+            assert!(verify_global_optimality(&net, &fingers));
+            let semi_chains = create_semi_chains(&net, &fingers);
+
+            for index_a in 0 .. num_nodes {
+                for index_b in index_a + 1 .. num_nodes {
+                    let src_id = *net.index_to_node(index_a).unwrap();
+                    let dst_id = *net.index_to_node(index_b).unwrap();
+                    assert!(find_path(src_id, dst_id, &net, &semi_chains).is_some());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_path_bidir_terminates() {
+        let seed: &[_] = &[1,2,3,4,9];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let g = 5;
+        let l = 2 * g + 1;
+
+        for net_type in 0 .. 5 {
+            let net = gen_network(net_type, g, l, 1, 2, &mut rng);
+            let mut fingers = init_fingers(&net, l, &mut rng);
+            converge_fingers(&net, &mut fingers, l);
+            assert!(verify_global_optimality(&net, &fingers));
+            let semi_chains = create_semi_chains(&net, &fingers);
+
+            for index_a in 0 .. net.igraph.node_count() {
+                for index_b in 0 .. net.igraph.node_count() {
+                    let src_id = *net.index_to_node(index_a).unwrap();
+                    let dst_id = *net.index_to_node(index_b).unwrap();
+
+                    // Make sure that ring distance strictly decreases at every hop:
+                    let mut cur_id = src_id;
+                    while cur_id != dst_id {
+                        let cur_semi_chains = &semi_chains[net.node_to_index(&cur_id).unwrap()];
+                        let next_id = next_hop_bidir(dst_id, cur_semi_chains, l).final_id;
+                        assert!(ring_dist(next_id, dst_id, l) < ring_dist(cur_id, dst_id, l));
+                        cur_id = next_id;
+                    }
+
+                    assert!(find_path_bidir(src_id, dst_id, &net, &semi_chains, l).is_some());
+                }
+            }
+        }
+    }
+
+    /// Converge fingers and route over a network with keys of type K.
+    fn check_wide_keys<K: RingKeyOps>(l: usize) {
+        let seed: &[_] = &[1,2,3,4,9,l];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let net = gen_network(0, 5, 11, 1, 2, &mut rng);
+        let wide_net: Network<K> = rekey_network(&net, l, &mut rng);
+
+        // Powers of 2 fingers exercise the full width of the key:
+        let finger_strategy = FingerStrategy::pow_fingers(l);
+        let mut fingers = init_fingers_by_strategy(&wide_net, l, 
+                                                   &finger_strategy, &mut rng);
+        converge_fingers(&wide_net, &mut fingers, l);
+        assert!(verify_global_optimality(&wide_net, &fingers));
+
+        let semi_chains = create_semi_chains(&wide_net, &fingers);
+        for src_index in 0 .. 8 {
+            for dst_index in 0 .. wide_net.igraph.node_count() {
+                let src_id = *wide_net.index_to_node(src_index).unwrap();
+                let dst_id = *wide_net.index_to_node(dst_index).unwrap();
+                assert!(find_path(src_id, dst_id, &wide_net, &semi_chains).is_some());
+                assert!(find_path_bidir(src_id, dst_id, &wide_net, &semi_chains, l).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn test_chord_wide_keys() {
+        check_wide_keys::<u64>(64);
+        check_wide_keys::<u128>(128);
+        check_wide_keys::<U256>(256);
+    }
 }
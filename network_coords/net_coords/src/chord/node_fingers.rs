@@ -1,58 +1,59 @@
 extern crate itertools;
 
 use chord::{RingKey, vdist};
+use chord::ring_key::{RingKeyOps};
 use std::collections::{HashSet, HashMap};
 
 #[derive(Clone, Hash, Eq, PartialEq, Debug)]
-pub struct SemiChain {
-    pub final_id: RingKey,
+pub struct SemiChain<K = RingKey> {
+    pub final_id: K,
     pub length: usize,
 }
 
 // Maintained finger:
 #[derive(Clone, Hash, Eq, PartialEq, Debug)]
-pub struct Finger {
-    pub target_id: RingKey,
-    pub schain: SemiChain,
+pub struct Finger<K = RingKey> {
+    pub target_id: K,
+    pub schain: SemiChain<K>,
     version: usize,
 }
 
-pub struct SortedFingersLeft {
-    pub sorted_fingers: Vec<Finger>,
+pub struct SortedFingersLeft<K = RingKey> {
+    pub sorted_fingers: Vec<Finger<K>>,
 }
 
-pub struct SortedFingersRight {
-    pub sorted_fingers: Vec<Finger>,
+pub struct SortedFingersRight<K = RingKey> {
+    pub sorted_fingers: Vec<Finger<K>>,
 }
 
-pub struct NodeFingers {
-    id: RingKey,
-    pub left: SortedFingersLeft,
-    pub right: SortedFingersRight,
+pub struct NodeFingers<K = RingKey> {
+    id: K,
+    pub left: SortedFingersLeft<K>,
+    pub right: SortedFingersRight<K>,
     version: usize, // Current version, used for caching.
-    updated_by: HashMap<RingKey, usize>,
+    updated_by: HashMap<K, usize>,
 }
 
 
 /// Check if proposed new chain is better for the right finger.
-fn is_right_finger_better(finger: &Finger, schain: &SemiChain, l:usize) -> bool {
+fn is_right_finger_better<K: RingKeyOps>(finger: &Finger<K>, schain: &SemiChain<K>, l:usize) -> bool {
     let cur_dist = (vdist(finger.target_id, finger.schain.final_id,l), finger.schain.length);
     let new_dist = (vdist(finger.target_id, schain.final_id,l), schain.length);
     new_dist < cur_dist
 }
 
 /// Check if proposed new chain is better for the left finger.
-fn is_left_finger_better(finger: &Finger, schain: &SemiChain, l:usize) -> bool {
+fn is_left_finger_better<K: RingKeyOps>(finger: &Finger<K>, schain: &SemiChain<K>, l:usize) -> bool {
     let cur_dist = (vdist(finger.schain.final_id, finger.target_id, l), finger.schain.length);
     let new_dist = (vdist(schain.final_id, finger.target_id, l), schain.length);
     new_dist < cur_dist
 }
 
 
-impl SortedFingersRight {
+impl<K: RingKeyOps> SortedFingersRight<K> {
     /// Add a new known chain, possibly update some fingers to use a new chain.
     /// Returns true if any chain was updated.
-    fn update(&mut self, schain: &SemiChain,l: usize, version: usize) -> bool {
+    fn update(&mut self, schain: &SemiChain<K>,l: usize, version: usize) -> bool {
         let mut has_changed: bool = false;
 
         let fingers_len = self.sorted_fingers.len();
@@ -74,7 +75,7 @@ impl SortedFingersRight {
     }
 
     /// Check if chosen semi chains tips are optimal with respect to target_id.
-    fn is_optimal(&self, sorted_keys: &Vec<RingKey>) -> bool {
+    fn is_optimal(&self, sorted_keys: &Vec<K>) -> bool {
         for fing in &self.sorted_fingers {
             let best_key = match sorted_keys.binary_search(&fing.target_id) {
                 Ok(index) => sorted_keys[index],
@@ -90,10 +91,10 @@ impl SortedFingersRight {
 
 }
 
-impl SortedFingersLeft {
+impl<K: RingKeyOps> SortedFingersLeft<K> {
     /// Add a new known chain, possibly update some fingers to use a new chain.
     /// Returns true if any chain was updated.
-    fn update(&mut self, schain: &SemiChain,l: usize, version: usize) -> bool {
+    fn update(&mut self, schain: &SemiChain<K>,l: usize, version: usize) -> bool {
         let mut has_changed: bool = false;
 
         let fingers_len = self.sorted_fingers.len();
@@ -116,7 +117,7 @@ impl SortedFingersLeft {
     }
 
     /// Check if chosen semi chains tips are optimal with respect to target_id.
-    fn is_optimal(&self, sorted_keys: &Vec<RingKey>) -> bool {
+    fn is_optimal(&self, sorted_keys: &Vec<K>) -> bool {
         for fing in &self.sorted_fingers {
             let best_key = match sorted_keys.binary_search(&fing.target_id) {
                 Ok(index) => sorted_keys[index],
@@ -133,9 +134,9 @@ impl SortedFingersLeft {
 }
 
 
-impl NodeFingers {
-    pub fn new(x_id: RingKey, target_ids_left: &Vec<RingKey>, 
-           target_ids_right: &Vec<RingKey>) -> NodeFingers {
+impl<K: RingKeyOps> NodeFingers<K> {
+    pub fn new(x_id: K, target_ids_left: &Vec<K>, 
+           target_ids_right: &Vec<K>) -> NodeFingers<K> {
 
         let mut nf = NodeFingers {
             id: x_id,
@@ -183,7 +184,7 @@ impl NodeFingers {
 
     /// Add a new known chain, possibly updating existing fingers.
     /// Returns true if any finger was updated.
-    pub fn update(&mut self, schain: &SemiChain, l: usize) -> bool {
+    pub fn update(&mut self, schain: &SemiChain<K>, l: usize) -> bool {
         let mut has_changed: bool = false;
         self.version += 1;
         has_changed |= self.left.update(&schain, l, self.version);
@@ -198,7 +199,7 @@ impl NodeFingers {
     }
 
     /// Check if fingers are keys global-optimal
-    pub fn is_optimal(&self, sorted_keys: &Vec<RingKey>) -> bool {
+    pub fn is_optimal(&self, sorted_keys: &Vec<K>) -> bool {
         if !self.left.is_optimal(&sorted_keys) {
             return false;
         }
@@ -210,8 +211,8 @@ impl NodeFingers {
 
     /// Get all node ids that this node is connected to using
     /// chains.
-    pub fn all_schains(&self) -> Vec<SemiChain> {
-        let mut unique_schains: HashSet<SemiChain> = HashSet::new();
+    pub fn all_schains(&self) -> Vec<SemiChain<K>> {
+        let mut unique_schains: HashSet<SemiChain<K>> = HashSet::new();
         for fing in &self.left.sorted_fingers {
             unique_schains.insert(fing.schain.clone());
         }
@@ -219,15 +220,15 @@ impl NodeFingers {
             unique_schains.insert(fing.schain.clone());
         }
 
-        let mut unique_schains_vec = unique_schains.into_iter().collect::<Vec<SemiChain>>();
+        let mut unique_schains_vec = unique_schains.into_iter().collect::<Vec<SemiChain<K>>>();
         unique_schains_vec.sort_by_key(|schain| (schain.final_id, schain.length));
         unique_schains_vec
     }
 
     /// Get all node ids that this node is connected to using
     /// chains.
-    pub fn all_fingers(&self) -> Vec<Finger> {
-        let mut unique_fingers: HashSet<Finger> = HashSet::new();
+    pub fn all_fingers(&self) -> Vec<Finger<K>> {
+        let mut unique_fingers: HashSet<Finger<K>> = HashSet::new();
         for fing in &self.left.sorted_fingers {
             unique_fingers.insert(fing.clone());
         }
@@ -235,7 +236,7 @@ impl NodeFingers {
             unique_fingers.insert(fing.clone());
         }
 
-        let mut unique_fingers_vec = unique_fingers.into_iter().collect::<Vec<Finger>>();
+        let mut unique_fingers_vec = unique_fingers.into_iter().collect::<Vec<Finger<K>>>();
         unique_fingers_vec.sort_by_key(|fing| 
                    (fing.target_id, fing.schain.final_id, fing.schain.length));
         unique_fingers_vec
@@ -245,7 +246,7 @@ impl NodeFingers {
     /// assuming that there is a connecting chain between the two
     /// of length chain_length.
     /// Return if any finger in self has changed.
-    pub fn update_by_fingers(&mut self, fingers_src: &NodeFingers, 
+    pub fn update_by_fingers(&mut self, fingers_src: &NodeFingers<K>, 
                  chain_length: usize, l:usize) -> bool {
 
         // Get last_version we have of fingers_src.
@@ -285,6 +286,25 @@ impl NodeFingers {
         has_changed
     }
 
+    /// Update finger's struct by a list of semi chains advertised by a remote node,
+    /// assuming that there is a connecting chain between the two of length chain_length.
+    /// Unlike update_by_fingers, no versions are tracked. 
+    /// Used for advertisements that do not originate from a NodeFingers struct.
+    /// Return if any finger in self has changed.
+    pub fn update_by_schains(&mut self, schains: &[SemiChain<K>],
+                 chain_length: usize, l: usize) -> bool {
+
+        let mut has_changed = false; // Has any finger changed?
+        for schain in schains {
+            let new_schain = SemiChain {
+                final_id: schain.final_id,
+                length: schain.length + chain_length,
+            };
+            has_changed |= self.update(&new_schain, l);
+        }
+        has_changed
+    }
+
 }
 
 
@@ -486,7 +506,7 @@ mod tests {
 
     #[test]
     fn test_node_fingers_basic() {
-        let mut nf = NodeFingers::new(6, &vec![1,3,7,11,54], &vec![5]);
+        let mut nf: NodeFingers = NodeFingers::new(6, &vec![1,3,7,11,54], &vec![5]);
         let sc = SemiChain {
             final_id: 3,
             length: 4
@@ -0,0 +1,309 @@
+extern crate rand;
+
+use std::hash::Hash;
+use std::fmt::Debug;
+
+use self::rand::{Rng};
+use self::rand::distributions::{IndependentSample, Range};
+
+/// Operations required from a key in the chord ring.
+/// The ring contains 2^l keys, where l <= num_bits().
+pub trait RingKeyOps: Copy + Clone + Ord + Eq + Hash + Debug {
+    /// Amount of bits in this key type.
+    fn num_bits() -> usize;
+    fn zero() -> Self;
+    /// 2^i. Requires i < num_bits()
+    fn pow2(i: usize) -> Self;
+    fn wrapping_add(self, other: Self) -> Self;
+    fn wrapping_sub(self, other: Self) -> Self;
+    fn bitxor(self, other: Self) -> Self;
+    /// Calculate self % 2^l
+    fn mod_pow2(self, l: usize) -> Self;
+    /// Get the bit at index i.
+    fn bit(self, i: usize) -> bool;
+    /// Find the msb bit index. Returns None for 0.
+    fn msb(self) -> Option<usize>;
+    /// Randomize a key in [0, 2^l)
+    fn rand_key<R: Rng>(l: usize, rng: &mut R) -> Self;
+    /// Randomize a key in [2^i, 2^(i+1))
+    fn rand_pow2_interval<R: Rng>(i: usize, rng: &mut R) -> Self {
+        Self::pow2(i).wrapping_add(Self::rand_key(i, rng))
+    }
+}
+
+impl RingKeyOps for u64 {
+    fn num_bits() -> usize {
+        64
+    }
+
+    fn zero() -> u64 {
+        0
+    }
+
+    fn pow2(i: usize) -> u64 {
+        assert!(i < 64);
+        1_u64 << i
+    }
+
+    fn wrapping_add(self, other: u64) -> u64 {
+        u64::wrapping_add(self, other)
+    }
+
+    fn wrapping_sub(self, other: u64) -> u64 {
+        u64::wrapping_sub(self, other)
+    }
+
+    fn bitxor(self, other: u64) -> u64 {
+        self ^ other
+    }
+
+    fn mod_pow2(self, l: usize) -> u64 {
+        if l >= 64 {
+            return self;
+        }
+        self % (1_u64 << l)
+    }
+
+    fn bit(self, i: usize) -> bool {
+        (self >> i) & 1 == 1
+    }
+
+    fn msb(self) -> Option<usize> {
+        match self {
+            0 => None,
+            _ => Some(63 - self.leading_zeros() as usize),
+        }
+    }
+
+    fn rand_key<R: Rng>(l: usize, rng: &mut R) -> u64 {
+        if l >= 64 {
+            return rng.gen::<u64>();
+        }
+        let rand_range: Range<u64> = Range::new(0u64,2_u64.pow(l as u32));
+        rand_range.ind_sample(rng)
+    }
+
+    fn rand_pow2_interval<R: Rng>(i: usize, rng: &mut R) -> u64 {
+        if i + 1 >= 64 {
+            return u64::pow2(i) | (rng.gen::<u64>() >> 1);
+        }
+        let rand_range: Range<u64> =
+            Range::new(2_u64.pow(i as u32),2_u64.pow((i + 1) as u32));
+        rand_range.ind_sample(rng)
+    }
+}
+
+/// Randomize a u128 where all bits are random.
+fn rand_u128<R: Rng>(rng: &mut R) -> u128 {
+    let high = rng.gen::<u64>() as u128;
+    let low = rng.gen::<u64>() as u128;
+    (high << 64) | low
+}
+
+impl RingKeyOps for u128 {
+    fn num_bits() -> usize {
+        128
+    }
+
+    fn zero() -> u128 {
+        0
+    }
+
+    fn pow2(i: usize) -> u128 {
+        assert!(i < 128);
+        1_u128 << i
+    }
+
+    fn wrapping_add(self, other: u128) -> u128 {
+        u128::wrapping_add(self, other)
+    }
+
+    fn wrapping_sub(self, other: u128) -> u128 {
+        u128::wrapping_sub(self, other)
+    }
+
+    fn bitxor(self, other: u128) -> u128 {
+        self ^ other
+    }
+
+    fn mod_pow2(self, l: usize) -> u128 {
+        if l >= 128 {
+            return self;
+        }
+        self % (1_u128 << l)
+    }
+
+    fn bit(self, i: usize) -> bool {
+        (self >> i) & 1 == 1
+    }
+
+    fn msb(self) -> Option<usize> {
+        match self {
+            0 => None,
+            _ => Some(127 - self.leading_zeros() as usize),
+        }
+    }
+
+    fn rand_key<R: Rng>(l: usize, rng: &mut R) -> u128 {
+        rand_u128(rng).mod_pow2(l)
+    }
+}
+
+/// A 256 bit unsigned integer. Big enough to contain a sha256 hash.
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub struct U256 {
+    // Field order matters for the derived Ord: high is compared first.
+    high: u128,
+    low: u128,
+}
+
+impl U256 {
+    pub fn new(high: u128, low: u128) -> U256 {
+        U256 {high, low}
+    }
+
+    /// Build a U256 from 32 big endian bytes.
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> U256 {
+        let mut high_bytes = [0u8; 16];
+        let mut low_bytes = [0u8; 16];
+        high_bytes.copy_from_slice(&bytes[.. 16]);
+        low_bytes.copy_from_slice(&bytes[16 ..]);
+        U256 {
+            high: u128::from_be_bytes(high_bytes),
+            low: u128::from_be_bytes(low_bytes),
+        }
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(x: u64) -> U256 {
+        U256 {high: 0, low: x as u128}
+    }
+}
+
+impl RingKeyOps for U256 {
+    fn num_bits() -> usize {
+        256
+    }
+
+    fn zero() -> U256 {
+        U256 {high: 0, low: 0}
+    }
+
+    fn pow2(i: usize) -> U256 {
+        assert!(i < 256);
+        if i >= 128 {
+            U256 {high: 1_u128 << (i - 128), low: 0}
+        } else {
+            U256 {high: 0, low: 1_u128 << i}
+        }
+    }
+
+    fn wrapping_add(self, other: U256) -> U256 {
+        let (low, carry) = self.low.overflowing_add(other.low);
+        let high = self.high.wrapping_add(other.high).wrapping_add(carry as u128);
+        U256 {high, low}
+    }
+
+    fn wrapping_sub(self, other: U256) -> U256 {
+        let (low, borrow) = self.low.overflowing_sub(other.low);
+        let high = self.high.wrapping_sub(other.high).wrapping_sub(borrow as u128);
+        U256 {high, low}
+    }
+
+    fn bitxor(self, other: U256) -> U256 {
+        U256 {
+            high: self.high ^ other.high,
+            low: self.low ^ other.low,
+        }
+    }
+
+    fn mod_pow2(self, l: usize) -> U256 {
+        if l >= 256 {
+            self
+        } else if l >= 128 {
+            U256 {high: self.high.mod_pow2(l - 128), low: self.low}
+        } else {
+            U256 {high: 0, low: self.low.mod_pow2(l)}
+        }
+    }
+
+    fn bit(self, i: usize) -> bool {
+        if i >= 128 {
+            self.high.bit(i - 128)
+        } else {
+            self.low.bit(i)
+        }
+    }
+
+    fn msb(self) -> Option<usize> {
+        match self.high.msb() {
+            Some(index) => Some(index + 128),
+            None => self.low.msb(),
+        }
+    }
+
+    fn rand_key<R: Rng>(l: usize, rng: &mut R) -> U256 {
+        let high = rand_u128(rng);
+        let low = rand_u128(rng);
+        U256 {high, low}.mod_pow2(l)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use self::rand::{StdRng};
+
+    #[test]
+    fn test_u64_msb() {
+        assert!(0_u64.msb().is_none());
+        assert!(1_u64.msb() == Some(0));
+        assert!(5_u64.msb() == Some(2));
+        assert!(u64::MAX.msb() == Some(63));
+    }
+
+    #[test]
+    fn test_mod_pow2_full_width() {
+        assert!(u64::MAX.mod_pow2(64) == u64::MAX);
+        assert!(u64::MAX.mod_pow2(4) == 15);
+        assert!(u128::MAX.mod_pow2(128) == u128::MAX);
+    }
+
+    #[test]
+    fn test_u256_add_sub() {
+        let a = U256::new(0, u128::MAX);
+        let one = U256::from(1);
+        assert!(a.wrapping_add(one) == U256::pow2(128));
+        assert!(U256::pow2(128).wrapping_sub(one) == a);
+        assert!(U256::zero().wrapping_sub(one).mod_pow2(256) ==
+                U256::new(u128::MAX, u128::MAX));
+        assert!(U256::zero().wrapping_sub(one).mod_pow2(130) ==
+                U256::new(3, u128::MAX));
+    }
+
+    #[test]
+    fn test_u256_order_msb() {
+        assert!(U256::pow2(128) > U256::new(0, u128::MAX));
+        assert!(U256::pow2(200).msb() == Some(200));
+        assert!(U256::pow2(3).msb() == Some(3));
+        assert!(U256::zero().msb().is_none());
+        assert!(U256::pow2(200).bit(200));
+        assert!(!U256::pow2(200).bit(199));
+    }
+
+    #[test]
+    fn test_rand_pow2_interval() {
+        let seed: &[_] = &[1,2,3,4];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        for i in 0 .. 64 {
+            let x = u64::rand_pow2_interval(i, &mut rng);
+            assert!(x.msb() == Some(i));
+        }
+        for i in 0 .. 256 {
+            let x = U256::rand_pow2_interval(i, &mut rng);
+            assert!(x.msb() == Some(i));
+        }
+    }
+}
@@ -1,22 +1,23 @@
 
 use std::collections::{HashSet};
 use chord::{RingKey, SemiChain};
+use chord::ring_key::{RingKeyOps};
 
 /// A chains array. Used for quick searching.
-pub struct SemiChainsArray {
-    schains: Vec<SemiChain>,
-    schains_set: HashSet<SemiChain>,
+pub struct SemiChainsArray<K = RingKey> {
+    schains: Vec<SemiChain<K>>,
+    schains_set: HashSet<SemiChain<K>>,
     is_indexed: bool,
 }
 
 /// Get the final id of the last SemiChain in a SemiRoute.
-pub fn schain_final_id(schain: &SemiChain) -> RingKey {
+pub fn schain_final_id<K: RingKeyOps>(schain: &SemiChain<K>) -> K {
     schain.final_id
 }
 
 
-impl SemiChainsArray {
-    pub fn new() -> SemiChainsArray {
+impl<K: RingKeyOps> SemiChainsArray<K> {
+    pub fn new() -> SemiChainsArray<K> {
         SemiChainsArray {
             schains: Vec::new(),
             schains_set: HashSet::new(),
@@ -25,7 +26,7 @@ impl SemiChainsArray {
     }
 
     /// Insert a new semi chain into the semi chains array.
-    pub fn insert_schain(&mut self, schain: SemiChain) {
+    pub fn insert_schain(&mut self, schain: SemiChain<K>) {
         assert!(!self.is_indexed, "Already indexed, aborting!");
         if self.schains_set.contains(&schain) {
             return
@@ -45,7 +46,7 @@ impl SemiChainsArray {
         self.is_indexed = true;
     }
 
-    pub fn find_closest_left(&self, target_id: RingKey) -> &SemiChain {
+    pub fn find_closest_left(&self, target_id: K) -> &SemiChain<K> {
         assert!(self.is_indexed, "Indexing is required before find_closest_right invocation!");
 
         let found_index = match self.schains.binary_search_by_key(&target_id, |schain| schain.final_id) {
@@ -66,7 +67,7 @@ impl SemiChainsArray {
         &self.schains[index]
     }
 
-    pub fn find_closest_right(&self, target_id: RingKey) -> &SemiChain {
+    pub fn find_closest_right(&self, target_id: K) -> &SemiChain<K> {
         assert!(self.is_indexed, "Indexing is required before find_closest_left invocation!");
         let found_index = match self.schains.binary_search_by_key(&target_id, |schain| schain.final_id) {
             Ok(index) => index,
@@ -96,7 +97,7 @@ mod tests {
 
     #[test]
     fn test_semi_chains_array() {
-        let mut semi_chains_array = SemiChainsArray::new();
+        let mut semi_chains_array: SemiChainsArray = SemiChainsArray::new();
         semi_chains_array.insert_schain(SemiChain{final_id: 3, length: 4});
         semi_chains_array.insert_schain(SemiChain{final_id: 4, length: 4});
         semi_chains_array.insert_schain(SemiChain{final_id: 5, length: 4});
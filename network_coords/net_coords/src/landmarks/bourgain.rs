@@ -0,0 +1,121 @@
+extern crate ring;
+
+use self::ring::{digest};
+
+use network::{Network};
+use landmarks::coords::{build_coords_by_sets};
+
+/// Check if node x is inside the set A_ij.
+/// A node x has probability 2^-j of being inside A_ij. Membership is decided by a hash,
+/// so every node can check the membership of every other node without communication.
+pub fn is_in_set(x: usize, i: usize, j: usize, seed: u64) -> bool {
+    assert!(j < 64, "j is too large!");
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(&seed.to_be_bytes());
+    ctx.update(&(x as u64).to_be_bytes());
+    ctx.update(&(i as u64).to_be_bytes());
+    ctx.update(&(j as u64).to_be_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&ctx.finish().as_ref()[.. 8]);
+    let val = u64::from_be_bytes(bytes);
+
+    // The first j bits are all zero with probability 2^-j:
+    j == 0 || (val >> (64 - j)) == 0
+}
+
+/// Amount of sets for Bourgain's theorem over a network of num_nodes nodes.
+/// Returns (m, t): Sets A_ij are defined for 0 <= i < m and 0 <= j < t.
+pub fn bourgain_dims(num_nodes: usize) -> (usize, usize) {
+    let t = (num_nodes as f64).log2().ceil() as usize;
+    (18 * t, t)
+}
+
+/// Coordinates based on Bourgain's theorem for metric embedding.
+/// Entry k of a coordinate is the distance to the closest member of the set sets[k].
+#[derive(Clone, Debug)]
+pub struct BourgainCoords {
+    /// The (i, j) of every set A_ij used as a coordinate entry.
+    /// Sets with no members are not used.
+    pub sets: Vec<(usize, usize)>,
+    /// Coordinates of all nodes.
+    pub coords: Vec<Vec<u64>>,
+}
+
+/// Build Bourgain coordinates for all nodes, using sets A_ij for 0 <= i < m and 0 <= j < t.
+/// Returns None if the network is not connected.
+pub fn build_bourgain_coords<Node>(net: &Network<Node>, m: usize, t: usize, seed: u64)
+    -> Option<BourgainCoords> {
+
+    let num_nodes = net.igraph.node_count();
+    let mut sets = Vec::new();
+    let mut members: Vec<Vec<bool>> = Vec::new();
+    for i in 0 .. m {
+        for j in 0 .. t {
+            let set_members = (0 .. num_nodes)
+                .map(|x| is_in_set(x, i, j, seed))
+                .collect::<Vec<bool>>();
+            if set_members.iter().any(|&is_member| is_member) {
+                sets.push((i, j));
+                members.push(set_members);
+            }
+        }
+    }
+
+    let coords = build_coords_by_sets(net, sets.len(), |v, c| members[c][v])?;
+    Some(BourgainCoords {
+        sets,
+        coords,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate rand;
+    use self::rand::{StdRng, SeedableRng};
+    use network_gen::{gen_network};
+    use landmarks::coord_mappers::{max_dist};
+
+    #[test]
+    fn test_is_in_set() {
+        let trials = 10000;
+        for &(j, seed) in &[(0, 0), (5, 0), (9, 1)] {
+            let count = (0 .. trials)
+                .filter(|&i| is_in_set(1, i, j, seed))
+                .count();
+            let ratio = (count as f64) / (trials as f64);
+            assert!((ratio - 2.0_f64.powi(-(j as i32))).abs() < 1.0 / (trials as f64).sqrt());
+        }
+        // Membership is deterministic:
+        assert!(is_in_set(3, 4, 2, 7) == is_in_set(3, 4, 2, 7));
+    }
+
+    #[test]
+    fn test_build_bourgain_coords() {
+        let seed: &[_] = &[1,2,3,4,12];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let g = 6;
+        let net = gen_network(0, g, 2 * g + 1, 0x10000, 0x20000, &mut rng);
+        let num_nodes = net.igraph.node_count();
+        let (m, t) = bourgain_dims(num_nodes);
+        assert!(t == g);
+
+        let bcoords = build_bourgain_coords(&net, m, t, 0).unwrap();
+        assert!(bcoords.coords.len() == num_nodes);
+        assert!(bcoords.sets.len() <= m * t);
+        // A_i0 contains all nodes:
+        assert!(bcoords.sets.iter().filter(|&&(_, j)| j == 0).count() == m);
+
+        // Coordinates never overestimate the distance:
+        for u in 0 .. 8 {
+            for v in 0 .. 8 {
+                let dist = net.dist(u, v).unwrap();
+                assert!(max_dist(&bcoords.coords[u], &bcoords.coords[v]) <= dist);
+            }
+        }
+
+        // Building is deterministic:
+        assert!(build_bourgain_coords(&net, m, t, 0).unwrap().coords == bcoords.coords);
+    }
+}
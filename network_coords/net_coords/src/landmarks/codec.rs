@@ -0,0 +1,186 @@
+/// Append x to buf as a variable length integer.
+/// Every byte holds 7 bits of x, least significant first. The high bit of a byte is set
+/// if more bytes follow.
+pub fn encode_varint(mut x: u64, buf: &mut Vec<u8>) {
+    while x >= 0x80 {
+        buf.push((x as u8 & 0x7f) | 0x80);
+        x >>= 7;
+    }
+    buf.push(x as u8);
+}
+
+/// Read a variable length integer from buf, starting at pos. pos is advanced past the integer.
+/// Returns None if buf is truncated or the integer does not fit in 64 bits.
+pub fn decode_varint(buf: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut x: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *buf.get(*pos)?;
+        *pos += 1;
+        if shift == 63 && byte > 1 {
+            // Too many bits:
+            return None;
+        }
+        x |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(x);
+        }
+        shift += 7;
+    }
+}
+
+/// Map a signed integer to an unsigned integer, so that integers close to 0
+/// get small values: 0, -1, 1, -2, 2, ... -> 0, 1, 2, 3, 4, ...
+pub fn zigzag_encode(x: i64) -> u64 {
+    ((x << 1) ^ (x >> 63)) as u64
+}
+
+pub fn zigzag_decode(x: u64) -> i64 {
+    ((x >> 1) as i64) ^ -((x & 1) as i64)
+}
+
+/// Encodes network coordinates into a compact wire format.
+///
+/// A coordinate is encoded as the index of a reference landmark (The closest landmark),
+/// followed by the difference between every entry of the coordinate and the matching entry
+/// of the reference landmark's coordinate. By the triangle inequality, those differences are
+/// bounded by the distance to the reference landmark. Differences are quantized and written
+/// as variable length integers.
+#[derive(Clone, Debug)]
+pub struct CoordCodec {
+    /// Coordinates of all landmarks. Every node knows those.
+    landmark_coords: Vec<Vec<u64>>,
+    /// Maximum error allowed in every entry of a decoded coordinate.
+    max_error: u64,
+}
+
+impl CoordCodec {
+    /// Create a codec. Using max_error = 0 gives a lossless encoding.
+    pub fn new(landmarks: &Vec<usize>, coords: &Vec<Vec<u64>>, max_error: u64) -> CoordCodec {
+        assert!(!landmarks.is_empty(), "At least one landmark is required!");
+        CoordCodec {
+            landmark_coords: landmarks.iter()
+                .map(|&landmark| coords[landmark].clone())
+                .collect(),
+            max_error,
+        }
+    }
+
+    /// Size of a quantization step.
+    fn quantum(&self) -> i64 {
+        (2 * self.max_error + 1) as i64
+    }
+
+    pub fn max_error(&self) -> u64 {
+        self.max_error
+    }
+
+    /// Encode a coordinate.
+    pub fn encode(&self, coord: &Vec<u64>) -> Vec<u8> {
+        assert!(coord.len() == self.landmark_coords.len(),
+                "Coordinate has a wrong amount of entries!");
+        let (ref_index, _) = coord.iter()
+            .enumerate()
+            .min_by_key(|&(i, &c)| (c, i))
+            .unwrap();
+
+        let quantum = self.quantum();
+        let mut buf = Vec::new();
+        encode_varint(ref_index as u64, &mut buf);
+        for (&c, &ref_c) in coord.iter().zip(&self.landmark_coords[ref_index]) {
+            let delta = (c as i64) - (ref_c as i64);
+            // Round to the closest multiple of quantum:
+            let qdelta = (delta + self.max_error as i64).div_euclid(quantum);
+            encode_varint(zigzag_encode(qdelta), &mut buf);
+        }
+        buf
+    }
+
+    /// Decode a coordinate.
+    /// Returns None if buf is not a valid encoding.
+    pub fn decode(&self, buf: &[u8]) -> Option<Vec<u64>> {
+        let mut pos = 0;
+        let ref_index = decode_varint(buf, &mut pos)? as usize;
+        let ref_coord = self.landmark_coords.get(ref_index)?;
+
+        let quantum = self.quantum();
+        let mut coord = Vec::with_capacity(ref_coord.len());
+        for &ref_c in ref_coord {
+            let qdelta = zigzag_decode(decode_varint(buf, &mut pos)?);
+            let c = (ref_c as i64).checked_add(qdelta.checked_mul(quantum)?)?;
+            // Quantization might take us slightly below 0:
+            coord.push(c.max(0) as u64);
+        }
+        if pos != buf.len() {
+            // Trailing bytes:
+            return None;
+        }
+        Some(coord)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate rand;
+    use self::rand::{StdRng, SeedableRng};
+    use network_gen::{gen_network};
+    use landmarks::coords::{build_coords, choose_landmarks};
+    use landmarks::coord_mappers::{max_dist};
+
+    #[test]
+    fn test_varint() {
+        for &x in &[0, 1, 0x7f, 0x80, 0x3fff, 0x4000, u64::MAX - 1, u64::MAX] {
+            let mut buf = Vec::new();
+            encode_varint(x, &mut buf);
+            let mut pos = 0;
+            assert!(decode_varint(&buf, &mut pos) == Some(x));
+            assert!(pos == buf.len());
+        }
+        let mut buf = Vec::new();
+        encode_varint(0x80, &mut buf);
+        assert!(buf == vec![0x80, 0x01]);
+        // Truncated:
+        assert!(decode_varint(&buf[.. 1], &mut 0).is_none());
+        // Too long:
+        assert!(decode_varint(&[0xff; 11], &mut 0).is_none());
+
+        for &x in &[0, 1, -1, 2, -2, i64::MAX, i64::MIN] {
+            assert!(zigzag_decode(zigzag_encode(x)) == x);
+        }
+        assert!(zigzag_encode(-1) == 1);
+        assert!(zigzag_encode(1) == 2);
+    }
+
+    #[test]
+    fn test_coord_codec() {
+        let seed: &[_] = &[1,2,3,4,11];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let g = 6;
+        let net = gen_network(0, g, 2 * g + 1, 0x10000, 0x20000, &mut rng);
+        let landmarks = choose_landmarks(&net, g * g, &mut rng);
+        let coords = build_coords(&net, &landmarks).unwrap();
+
+        let lossless = CoordCodec::new(&landmarks, &coords, 0);
+        for coord in &coords {
+            let buf = lossless.encode(coord);
+            assert!(buf.len() < coord.len() * 8);
+            assert!(lossless.decode(&buf).as_ref() == Some(coord));
+            // Truncated or padded encodings are invalid:
+            assert!(lossless.decode(&buf[.. buf.len() - 1]).is_none());
+            let mut padded = buf.clone();
+            padded.push(0);
+            assert!(lossless.decode(&padded).is_none());
+        }
+
+        let max_error = 0x1000;
+        let lossy = CoordCodec::new(&landmarks, &coords, max_error);
+        for coord in &coords {
+            let buf = lossy.encode(coord);
+            assert!(buf.len() <= lossless.encode(coord).len());
+            let decoded = lossy.decode(&buf).unwrap();
+            assert!(max_dist(coord, &decoded) <= max_error);
+        }
+    }
+}
@@ -8,6 +8,7 @@ use self::num::Complex;
 use std::f64;
 use statistic::Stream;
 use self::itertools::Itertools;
+use chord::{RingKey};
 
 /// Convert network coordinate to chord value in [0,1) 
 /// by projection to a plane.
@@ -86,16 +87,130 @@ pub fn coord_to_ring_adj_pairs(coord: &Vec<u64>) -> f64 {
     f
 }
 
+/// Convert network coordinate to chord value in [0,1).
+/// Landmark i is placed at angle 2*pi*i/k, and gets weight exp(-2x), where x is the distance
+/// to landmark i, relative to the closest landmark and divided by the average relative
+/// distance. Without the division, all landmarks except the closest one get negligible
+/// weights for realistic distances, and the value depends only on the closest landmark.
+/// See also RingMapper, which places landmarks along a tour.
 pub fn coord_to_ring(coord: &Vec<u64>) -> f64 {
     let k: f64 = coord.len() as f64;
     let ang_part = (2.0 * f64::consts::PI) / k;
+    let min_x = *coord.iter().min().unwrap();
+    let scale = (coord.iter().map(|&a| (a - min_x) as f64).sum::<f64>() / k).max(1.0);
 
     let sum: Complex<f64> = 
-        coord.iter().map(|&a| a as f64).enumerate()
+        coord.iter().map(|&a| ((a - min_x) as f64) / scale).enumerate()
             .fold(Complex::new(0.0,0.0), |acc, (i,x)|
                 acc + Complex::from_polar(&((-x*2.0).exp()),&(ang_part * (i as f64))));
 
-    (sum.arg() + f64::consts::PI) / (2.0 * f64::consts::PI)
+    ((sum.arg() + f64::consts::PI) / (2.0 * f64::consts::PI)).fract()
+}
+
+/// Maps landmarks coordinates to keys on a chord ring, so that nodes that are close in the
+/// network get close keys.
+///
+/// Landmarks are ordered along a short tour, and spread evenly around the ring in this order.
+/// A node's key is a weighted circular mean of the landmarks' places on the ring, where closer
+/// landmarks get exponentially larger weights.
+#[derive(Clone, Debug)]
+pub struct RingMapper {
+    /// Place of every landmark on the ring, as a fraction in [0,1).
+    landmark_places: Vec<f64>,
+    /// Distance in which the weight of a landmark decays by a factor of e.
+    scale: f64,
+    /// Amount of bits in a ring key.
+    l: usize,
+}
+
+impl RingMapper {
+    pub fn new(landmarks: &Vec<usize>, coords: &Vec<Vec<u64>>, l: usize) -> RingMapper {
+        assert!(!landmarks.is_empty(), "At least one landmark is required!");
+        assert!(l > 0 && l <= 64, "Invalid amount of key bits!");
+        let num_landmarks = landmarks.len();
+        let lm_dist = |i: usize, j: usize| coords[landmarks[i]][j];
+
+        // Nearest neighbor tour over the landmarks, starting from the first landmark:
+        let mut tour = vec![0];
+        let mut in_tour = vec![false; num_landmarks];
+        in_tour[0] = true;
+        let mut sum_tour_dist: u64 = 0;
+        while tour.len() < num_landmarks {
+            let last = *tour.last().unwrap();
+            let next = (0 .. num_landmarks)
+                .filter(|&j| !in_tour[j])
+                .min_by_key(|&j| (lm_dist(last, j), j))
+                .unwrap();
+            sum_tour_dist += lm_dist(last, next);
+            in_tour[next] = true;
+            tour.push(next);
+        }
+
+        let mut landmark_places = vec![0.0; num_landmarks];
+        for (pos, &i) in tour.iter().enumerate() {
+            landmark_places[i] = (pos as f64) / (num_landmarks as f64);
+        }
+
+        // Weights decay on the scale of the distance between adjacent landmarks on the tour:
+        let scale = if num_landmarks > 1 {
+            ((sum_tour_dist as f64) / ((num_landmarks - 1) as f64)).max(1.0)
+        } else {
+            1.0
+        };
+
+        RingMapper {
+            landmark_places,
+            scale,
+            l,
+        }
+    }
+
+    /// Map a coordinate to a value in [0,1).
+    pub fn coord_to_fraction(&self, coord: &Vec<u64>) -> f64 {
+        assert!(coord.len() == self.landmark_places.len(),
+                "Coordinate has a wrong amount of entries!");
+        let (closest, &min_x) = coord.iter()
+            .enumerate()
+            .min_by_key(|&(i, &x)| (x, i))
+            .unwrap();
+
+        // Relative to the closest landmark, so that its weight is 1 and the sum can
+        // not underflow:
+        let sum: Complex<f64> = coord.iter()
+            .zip(&self.landmark_places)
+            .map(|(&x, &place)| Complex::from_polar(
+                    &(-((x - min_x) as f64) / self.scale).exp(),
+                    &(2.0 * f64::consts::PI * place)))
+            .fold(Complex::new(0.0, 0.0), |acc, z| acc + z);
+
+        if sum.norm() < 1e-9 {
+            // Weights cancel out. Use the closest landmark:
+            return self.landmark_places[closest];
+        }
+        let fraction = sum.arg() / (2.0 * f64::consts::PI);
+        if fraction < 0.0 {
+            (fraction + 1.0).fract()
+        } else {
+            fraction
+        }
+    }
+
+    /// Map a coordinate to a key on a ring of 2^l keys.
+    pub fn coord_to_key(&self, coord: &Vec<u64>) -> RingKey {
+        fraction_to_key(self.coord_to_fraction(coord), self.l)
+    }
+}
+
+/// Convert a value in [0,1) to a key on a ring of 2^l keys.
+pub fn fraction_to_key(fraction: f64, l: usize) -> RingKey {
+    assert!(l > 0 && l <= 64, "Invalid amount of key bits!");
+    let key = (fraction * 2.0_f64.powi(l as i32)) as RingKey;
+    if l == 64 {
+        key
+    } else {
+        // fraction might be rounded up to 1.0:
+        key.min((1 << l) - 1)
+    }
 }
 
 
@@ -219,3 +334,125 @@ pub fn approx_pairs_dist1_normalized(u: usize, v: usize, coords: &Vec<Vec<u64>>,
         .sum()
 
 }
+
+/// A way to approximate the distance between two nodes of a network.
+pub trait CoordMetric {
+    /// Approximate distance between the nodes with indices u and v.
+    fn approx_dist(&self, u: usize, v: usize) -> f64;
+
+    /// Lower and upper bounds on the distance between u and v.
+    /// None if the metric can not bound the distance.
+    fn dist_bounds(&self, u: usize, v: usize) -> Option<(f64, f64)> {
+        let _ = (u, v);
+        None
+    }
+}
+
+/// Any function of two node indices is a metric. It can not bound distances.
+impl<F> CoordMetric for F where F: Fn(usize, usize) -> f64 {
+    fn approx_dist(&self, u: usize, v: usize) -> f64 {
+        self(u, v)
+    }
+}
+
+/// A metric that applies a mapper (Like approx_max_dist) over landmarks coordinates, as
+/// created by build_coords. Entry i of every coordinate is the distance to landmark i,
+/// so the triangle inequality bounds the distance between two nodes.
+pub struct CoordsMetric<'a, F> {
+    pub coords: &'a Vec<Vec<u64>>,
+    pub landmarks: &'a Vec<usize>,
+    pub mapper: F,
+}
+
+impl<'a, F> CoordMetric for CoordsMetric<'a, F> 
+    where F: Fn(usize, usize, &Vec<Vec<u64>>, &Vec<usize>) -> f64 {
+
+    fn approx_dist(&self, u: usize, v: usize) -> f64 {
+        (self.mapper)(u, v, self.coords, self.landmarks)
+    }
+
+    /// |d(u,l) - d(v,l)| <= d(u,v) <= d(u,l) + d(v,l) for every landmark l.
+    fn dist_bounds(&self, u: usize, v: usize) -> Option<(f64, f64)> {
+        let lower = max_dist(&self.coords[u], &self.coords[v]);
+        let upper = self.coords[u].iter()
+            .zip(&self.coords[v])
+            .map(|(&a, &b)| a + b)
+            .min()?;
+        Some((lower as f64, upper as f64))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use network::{grid2_net};
+    use landmarks::coords::{build_coords};
+    use chord::{ring_dist};
+
+    #[test]
+    fn test_coord_to_ring_large_distances() {
+        // Realistic distances used to underflow all weights, mapping every coordinate to
+        // the same value:
+        let a = coord_to_ring(&vec![0x10000, 0x20000, 0x30000]);
+        let b = coord_to_ring(&vec![0x30000, 0x10000, 0x20000]);
+        assert!((0.0 .. 1.0).contains(&a));
+        assert!((0.0 .. 1.0).contains(&b));
+        assert!(a != b);
+
+        // The same closest landmark, but different distances to the other landmarks:
+        let c = coord_to_ring(&vec![0x10000, 0x20000, 0x30000, 0x40000]);
+        let d = coord_to_ring(&vec![0x10000, 0x40000, 0x30000, 0x20000]);
+        let e = coord_to_ring(&vec![0x10000, 0x28000, 0x30000, 0x40000]);
+        assert!(c != d);
+        assert!(c != e);
+        assert!(d != e);
+    }
+
+    #[test]
+    fn test_fraction_to_key() {
+        assert!(fraction_to_key(0.0, 8) == 0);
+        assert!(fraction_to_key(0.5, 8) == 0x80);
+        assert!(fraction_to_key(0.9999999999999999, 8) == 0xff);
+        assert!(fraction_to_key(0.5, 64) == 1 << 63);
+    }
+
+    #[test]
+    fn test_ring_mapper_locality() {
+        let k = 12;
+        let l = 32;
+        let net = grid2_net(k);
+        let landmarks = vec![0, 5, 11, 60, 66, 71, 132, 137, 143];
+        let coords = build_coords(&net, &landmarks).unwrap();
+        let mapper = RingMapper::new(&landmarks, &coords, l);
+
+        let keys = coords.iter()
+            .map(|coord| mapper.coord_to_key(coord))
+            .collect::<Vec<RingKey>>();
+        assert!(keys.iter().all(|&key| key < (1 << l)));
+
+        // A landmark maps closer to its own place on the ring than to the place of any
+        // other landmark:
+        let half_spacing = 0.5 / (landmarks.len() as f64);
+        for (i, &landmark) in landmarks.iter().enumerate() {
+            let diff = (mapper.coord_to_fraction(&coords[landmark])
+                        - mapper.landmark_places[i]).abs();
+            assert!(diff.min(1.0 - diff) < half_spacing);
+        }
+
+        // Adjacent nodes get much closer keys than arbitrary nodes:
+        let avg_ring_dist = |pairs: &[(usize, usize)]| pairs.iter()
+            .map(|&(u, v)| ring_dist(keys[u], keys[v], l) as f64)
+            .sum::<f64>() / (pairs.len() as f64);
+        let adjacent_pairs = net.igraph.all_edges()
+            .map(|(u, v, _)| (u, v))
+            .collect::<Vec<(usize, usize)>>();
+        let arbitrary_pairs = (0 .. k * k)
+            .map(|u| (u, (u * 37 + 11) % (k * k)))
+            .collect::<Vec<(usize, usize)>>();
+        assert!(avg_ring_dist(&adjacent_pairs) * 4.0 < avg_ring_dist(&arbitrary_pairs));
+
+        // Deterministic:
+        assert!(RingMapper::new(&landmarks, &coords, l).coord_to_key(&coords[7]) == keys[7]);
+    }
+}
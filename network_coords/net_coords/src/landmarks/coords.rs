@@ -49,14 +49,22 @@ fn iter_coords<Node>(net: &Network<Node>, work_coords: &mut Vec<Vec<Option<u64>>
 /// Returns true if anything in the coords state has changed.
 pub fn build_coords<Node>(net: &Network<Node>, landmarks:&Vec<usize>) -> Option<Vec<Vec<u64>>> 
 {
+    build_coords_by_sets(net, landmarks.len(), |v, c| landmarks[c] == v)
+}
+
+/// Build coordinates where entry c of every coordinate is the distance to the closest
+/// member of a set of nodes. is_member(v, c) tells if node v is a member of set c.
+/// Returns None if some node can not reach some set (Or if some set is empty).
+pub fn build_coords_by_sets<Node, F>(net: &Network<Node>, num_sets: usize, is_member: F) 
+    -> Option<Vec<Vec<u64>>> where F: Fn(usize, usize) -> bool {
 
     let mut work_coords: Vec<Vec<Option<u64>>> = Vec::new();
 
     // Initialize coordinates:
     for v in net.igraph.nodes() {
         let mut v_coords = Vec::new();
-        for &l in landmarks.iter() {
-            if v != l {
+        for c in 0 .. num_sets {
+            if !is_member(v, c) {
                 v_coords.push(None)
             } else {
                 v_coords.push(Some(0))
@@ -0,0 +1,161 @@
+extern crate rand;
+
+use std::hash::Hash;
+
+use self::rand::{Rng};
+
+use network::{Network};
+use random_util::{choose_k_nums};
+use statistic::{pearson, spearman};
+use landmarks::coord_mappers::{CoordMetric};
+
+/// Percentiles of the relative error reported by distortion_report.
+pub const REL_ERROR_PERCENTILES: [f64; 4] = [50.0, 90.0, 99.0, 100.0];
+
+/// How well a metric predicts real distances in a network, over a sample of node pairs.
+#[derive(Clone, Debug)]
+pub struct DistortionReport {
+    pub num_pairs: usize,
+    /// Pearson correlation between predicted and real distances.
+    pub pearson: f64,
+    /// Spearman (Rank) correlation between predicted and real distances.
+    pub spearman: f64,
+    /// Relative error |approx - dist| / dist at every percentile of REL_ERROR_PERCENTILES.
+    pub rel_error_percentiles: Vec<f64>,
+    /// Fraction of pairs where the lower bound equals the real distance.
+    /// None if the metric can not bound distances.
+    pub lower_tight_ratio: Option<f64>,
+    /// Fraction of pairs where the upper bound equals the real distance.
+    /// None if the metric can not bound distances.
+    pub upper_tight_ratio: Option<f64>,
+}
+
+/// Get the p-th percentile of sorted_values, using the nearest rank method.
+pub fn percentile(sorted_values: &[f64], p: f64) -> f64 {
+    assert!(!sorted_values.is_empty(), "No values!");
+    assert!((0.0 ..= 100.0).contains(&p), "Invalid percentile!");
+    let rank = ((p / 100.0) * (sorted_values.len() as f64)).ceil() as usize;
+    sorted_values[rank.max(1) - 1]
+}
+
+/// Choose num_pairs random pairs of distinct nodes.
+pub fn sample_node_pairs<R: Rng>(num_nodes: usize, num_pairs: usize, rng: &mut R)
+    -> Vec<(usize, usize)> {
+
+    (0 .. num_pairs)
+        .map(|_| {
+            let mut node_pair = choose_k_nums(2, num_nodes, rng)
+                .into_iter().collect::<Vec<usize>>();
+            // Sort for determinism:
+            node_pair.sort();
+            (node_pair[0], node_pair[1])
+        })
+        .collect()
+}
+
+/// Measure the distortion of metric over the given pairs of distinct nodes.
+pub fn distortion_report<Node: Hash + Eq + Clone, M: CoordMetric + ?Sized>(
+        net: &Network<Node>, metric: &M, node_pairs: &[(usize, usize)]) -> DistortionReport {
+
+    assert!(node_pairs.len() > 1, "At least two pairs are required!");
+    let mut dists: Vec<f64> = Vec::new();
+    let mut approx_dists: Vec<f64> = Vec::new();
+    let mut rel_errors: Vec<f64> = Vec::new();
+    let mut num_bounded = 0;
+    let mut num_lower_tight = 0;
+    let mut num_upper_tight = 0;
+
+    for &(u, v) in node_pairs {
+        assert!(u != v, "Node pairs must be distinct!");
+        let dist = net.dist(u, v).unwrap() as f64;
+        let approx_dist = metric.approx_dist(u, v);
+        dists.push(dist);
+        approx_dists.push(approx_dist);
+        rel_errors.push((approx_dist - dist).abs() / dist);
+
+        if let Some((lower, upper)) = metric.dist_bounds(u, v) {
+            num_bounded += 1;
+            if lower == dist {
+                num_lower_tight += 1;
+            }
+            if upper == dist {
+                num_upper_tight += 1;
+            }
+        }
+    }
+    rel_errors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let bounded_ratio = |num_tight: usize| if num_bounded == 0 {
+        None
+    } else {
+        Some((num_tight as f64) / (num_bounded as f64))
+    };
+
+    DistortionReport {
+        num_pairs: node_pairs.len(),
+        pearson: pearson(&approx_dists, &dists).unwrap(),
+        spearman: spearman(&approx_dists, &dists).unwrap(),
+        rel_error_percentiles: REL_ERROR_PERCENTILES.iter()
+            .map(|&p| percentile(&rel_errors, p))
+            .collect(),
+        lower_tight_ratio: bounded_ratio(num_lower_tight),
+        upper_tight_ratio: bounded_ratio(num_upper_tight),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use self::rand::{StdRng, SeedableRng};
+    use network::{grid2_net};
+    use landmarks::coords::{build_coords};
+    use landmarks::coord_mappers::{CoordsMetric, approx_max_dist};
+
+    #[test]
+    fn test_percentile() {
+        let values = [1.0, 2.0, 3.0, 4.0];
+        assert!(percentile(&values, 0.0) == 1.0);
+        assert!(percentile(&values, 50.0) == 2.0);
+        assert!(percentile(&values, 51.0) == 3.0);
+        assert!(percentile(&values, 100.0) == 4.0);
+    }
+
+    #[test]
+    fn test_distortion_report() {
+        let seed: &[_] = &[1,2,3,4,14];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let k = 5;
+        let net = grid2_net(k);
+        let node_pairs = sample_node_pairs(k * k, 50, &mut rng);
+
+        // The exact distance has no distortion:
+        let exact = |u: usize, v: usize| net.dist(u, v).unwrap() as f64;
+        let report = distortion_report(&net, &exact, &node_pairs);
+        assert!(report.num_pairs == 50);
+        assert!((report.pearson - 1.0).abs() < 1e-9);
+        assert!(report.rel_error_percentiles.iter().all(|&e| e == 0.0));
+        assert!(report.lower_tight_ratio.is_none());
+
+        // Two opposite corners of the grid as landmarks. Lower bounds are tight only on
+        // some pairs, but upper bounds are always valid:
+        let landmarks = vec![0, k * k - 1];
+        let coords = build_coords(&net, &landmarks).unwrap();
+        let metric = CoordsMetric {
+            coords: &coords,
+            landmarks: &landmarks,
+            mapper: |u, v, coords: &Vec<Vec<u64>>, landmarks: &Vec<usize>|
+                approx_max_dist(u, v, coords, landmarks) as f64,
+        };
+        for &(u, v) in &node_pairs {
+            let (lower, upper) = metric.dist_bounds(u, v).unwrap();
+            let dist = net.dist(u, v).unwrap() as f64;
+            assert!(lower <= dist && dist <= upper);
+        }
+        let report = distortion_report(&net, &metric, &node_pairs);
+        let lower_tight_ratio = report.lower_tight_ratio.unwrap();
+        assert!(lower_tight_ratio > 0.0 && lower_tight_ratio < 1.0);
+        assert!(report.upper_tight_ratio.is_some());
+        assert!(report.rel_error_percentiles[0] <= report.rel_error_percentiles[3]);
+    }
+}
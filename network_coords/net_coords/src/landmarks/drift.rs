@@ -0,0 +1,240 @@
+extern crate rand;
+
+use std::hash::Hash;
+
+use self::rand::{Rng};
+use self::rand::distributions::{IndependentSample, Range};
+
+use network::{Network};
+use landmarks::coord_mappers::{max_dist};
+
+/// Apply num_changes random topology changes to the network.
+/// Every change is one of: Adding a new edge, removing an edge or changing the weight of an
+/// edge. New weights are chosen in [min_weight, max_weight). The network stays connected.
+pub fn change_topology<Node: Hash + Eq + Clone, R: Rng>(net: &mut Network<Node>,
+        num_changes: usize, min_weight: u64, max_weight: u64, rng: &mut R) {
+
+    let num_nodes = net.igraph.node_count();
+    assert!(num_nodes >= 2, "Not enough nodes to change the topology!");
+    let node_range: Range<usize> = Range::new(0, num_nodes);
+    let weight_range: Range<u64> = Range::new(min_weight, max_weight);
+    let change_range: Range<usize> = Range::new(0, 3);
+
+    let mut num_done = 0;
+    while num_done < num_changes {
+        let a = node_range.ind_sample(rng);
+        match change_range.ind_sample(rng) {
+            // Add an edge:
+            0 => {
+                let b = node_range.ind_sample(rng);
+                if a == b || net.igraph.contains_edge(a, b) {
+                    continue;
+                }
+                net.igraph.add_edge(a, b, weight_range.ind_sample(rng));
+            },
+            // Remove an edge, only if the network stays connected:
+            1 => {
+                let neighbors = net.igraph.neighbors(a).collect::<Vec<usize>>();
+                if neighbors.is_empty() {
+                    continue;
+                }
+                let nei_range: Range<usize> = Range::new(0, neighbors.len());
+                let b = neighbors[nei_range.ind_sample(rng)];
+                let weight = net.igraph.remove_edge(a, b).unwrap();
+                if !net.is_connected() {
+                    net.igraph.add_edge(a, b, weight);
+                    continue;
+                }
+            },
+            // Change the weight of an edge:
+            2 => {
+                let neighbors = net.igraph.neighbors(a).collect::<Vec<usize>>();
+                if neighbors.is_empty() {
+                    continue;
+                }
+                let nei_range: Range<usize> = Range::new(0, neighbors.len());
+                let b = neighbors[nei_range.ind_sample(rng)];
+                net.igraph.add_edge(a, b, weight_range.ind_sample(rng));
+            },
+            _ => unreachable!(),
+        }
+        num_done += 1;
+    }
+}
+
+/// Staleness of an advertised coordinate: Its distance from the freshly recomputed coordinate.
+pub fn coord_staleness(advertised_coord: &Vec<u64>, fresh_coord: &Vec<u64>) -> u64 {
+    max_dist(advertised_coord, fresh_coord)
+}
+
+/// Staleness of the advertised coordinates of all nodes.
+pub fn coords_staleness(advertised_coords: &[Vec<u64>], fresh_coords: &[Vec<u64>]) -> Vec<u64> {
+    assert!(advertised_coords.len() == fresh_coords.len(),
+            "Different amount of advertised and fresh coordinates!");
+    advertised_coords.iter()
+        .zip(fresh_coords)
+        .map(|(advertised_coord, fresh_coord)| coord_staleness(advertised_coord, fresh_coord))
+        .collect()
+}
+
+/// Result of trying to find a node using an approximate coordinate.
+#[derive(Clone, Debug)]
+pub struct LastMileResult {
+    /// Was the wanted node found?
+    pub found: bool,
+    /// The node closest to the approximate coordinate that routing has reached.
+    pub best_match: usize,
+    /// Length of the path to best_match.
+    pub approx_path_len: u64,
+    /// Distance in hops (Ignoring weights) from best_match to the wanted node.
+    /// Meaningful only if found is true.
+    pub last_mile_hops: u64,
+}
+
+/// Find dst_node given an approximate coordinate of dst_node.
+/// First route to the node closest to approx_dst_coord using route, and then search for
+/// dst_node in the closest last_mile_area nodes around it.
+/// route returns the node where the coordinate landed, and the length of the path to it.
+pub fn find_node_last_mile<Node, FT>(net: &Network<Node>, dst_node: usize,
+        approx_dst_coord: &Vec<u64>, last_mile_area: usize, route: FT) -> LastMileResult
+    where Node: Hash + Eq + Clone, FT: FnOnce(&Vec<u64>) -> (usize, u64) {
+
+    let (best_match, approx_path_len) = route(approx_dst_coord);
+
+    let opt_last_mile_hops = if best_match == dst_node {
+        Some(0)
+    } else {
+        net.closest_nodes_structure(best_match)
+            .take(last_mile_area)
+            .find(|&(i, _, _)| i == dst_node)
+            .map(|(_, hops, _)| hops)
+    };
+
+    LastMileResult {
+        found: opt_last_mile_hops.is_some(),
+        best_match,
+        approx_path_len,
+        last_mile_hops: opt_last_mile_hops.unwrap_or(0),
+    }
+}
+
+/// Routing success rates, grouped by the staleness of the destination coordinate.
+#[derive(Clone, Debug)]
+pub struct StalenessHistogram {
+    /// Size of the staleness range of every bucket.
+    pub bucket_size: u64,
+    /// Amount of routing attempts in every bucket.
+    pub num_attempts: Vec<usize>,
+    /// Amount of successful routing attempts in every bucket.
+    pub num_found: Vec<usize>,
+}
+
+impl StalenessHistogram {
+    pub fn new(bucket_size: u64) -> StalenessHistogram {
+        assert!(bucket_size > 0, "Bucket size must be positive!");
+        StalenessHistogram {
+            bucket_size,
+            num_attempts: Vec::new(),
+            num_found: Vec::new(),
+        }
+    }
+
+    /// Add the result of routing to a coordinate with the given staleness.
+    pub fn add(&mut self, staleness: u64, found: bool) {
+        let bucket = (staleness / self.bucket_size) as usize;
+        if bucket >= self.num_attempts.len() {
+            self.num_attempts.resize(bucket + 1, 0);
+            self.num_found.resize(bucket + 1, 0);
+        }
+        self.num_attempts[bucket] += 1;
+        if found {
+            self.num_found[bucket] += 1;
+        }
+    }
+
+    /// Success ratio of a bucket. None if the bucket has no attempts.
+    pub fn success_ratio(&self, bucket: usize) -> Option<f64> {
+        match self.num_attempts.get(bucket) {
+            None | Some(&0) => None,
+            Some(&num_attempts) =>
+                Some((self.num_found[bucket] as f64) / (num_attempts as f64)),
+        }
+    }
+
+    /// Total success ratio over all buckets.
+    pub fn total_success_ratio(&self) -> f64 {
+        let num_attempts: usize = self.num_attempts.iter().sum();
+        let num_found: usize = self.num_found.iter().sum();
+        (num_found as f64) / (num_attempts as f64)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use self::rand::{StdRng, SeedableRng};
+    use network_gen::{gen_network};
+    use landmarks::{gen_areas, find_path_landmarks_areas_by_coord};
+    use landmarks::coords::{build_coords, choose_landmarks};
+
+    #[test]
+    fn test_staleness_histogram() {
+        let mut histogram = StalenessHistogram::new(10);
+        histogram.add(0, true);
+        histogram.add(5, false);
+        histogram.add(25, true);
+
+        assert!(histogram.num_attempts == vec![2, 0, 1]);
+        assert!(histogram.success_ratio(0) == Some(0.5));
+        assert!(histogram.success_ratio(1).is_none());
+        assert!(histogram.success_ratio(2) == Some(1.0));
+        assert!(histogram.success_ratio(3).is_none());
+        assert!((histogram.total_success_ratio() - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_stale_coords_last_mile() {
+        let seed: &[_] = &[1,2,3,4,10];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let g = 6;
+        let mut net = gen_network(0, g, 2 * g + 1, 0x10000, 0x20000, &mut rng);
+        let landmarks = choose_landmarks(&net, g * g, &mut rng);
+        let old_coords = build_coords(&net, &landmarks).unwrap();
+
+        change_topology(&mut net, 2 * g, 0x10000, 0x20000, &mut rng);
+        assert!(net.is_connected());
+        let areas = gen_areas(g * g, &net);
+        let coords = build_coords(&net, &landmarks).unwrap();
+        let staleness = coords_staleness(&old_coords, &coords);
+        assert!(staleness.iter().any(|&s| s > 0));
+
+        let node_count = net.igraph.node_count();
+        let mut histogram = StalenessHistogram::new(0x10000);
+        for i in 0 .. 20 {
+            let src_node = (i * 7) % node_count;
+            let dst_node = (i * 13 + 5) % node_count;
+
+            let mut route = |coord: &Vec<u64>| {
+                let (best_match, path_len, _) = find_path_landmarks_areas_by_coord(
+                    src_node, coord, 2, &net, &coords, &landmarks, &areas, &mut rng);
+                (best_match, path_len)
+            };
+
+            // Fresh coordinates with no last mile search:
+            let result = find_node_last_mile(&net, dst_node, &coords[dst_node], 0, &mut route);
+            if result.found {
+                assert!(result.best_match == dst_node);
+                assert!(result.last_mile_hops == 0);
+            }
+
+            // A last mile search over the whole network always finds the node:
+            let result = find_node_last_mile(&net, dst_node, &old_coords[dst_node], node_count,
+                                             &mut route);
+            assert!(result.found);
+            histogram.add(coord_staleness(&old_coords[dst_node], &coords[dst_node]),
+                          result.found);
+        }
+        assert!(histogram.total_success_ratio() == 1.0);
+    }
+}
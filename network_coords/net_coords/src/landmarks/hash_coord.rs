@@ -0,0 +1,122 @@
+extern crate rand;
+extern crate ring;
+
+use self::rand::{Rng};
+use self::ring::{digest};
+
+use landmarks::randomize_coord::{RandomizeStrategy, randomize_coord_by_strategy};
+
+/// A deterministic stream of random looking bytes, derived from a key.
+/// Block i of the stream is SHA256(SHA256(key) || i).
+pub struct HashRng {
+    seed: [u8; 32],
+    counter: u64,
+    block: [u8; 32],
+    block_pos: usize,
+}
+
+impl HashRng {
+    pub fn new(key: &[u8]) -> HashRng {
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(digest::digest(&digest::SHA256, key).as_ref());
+        HashRng {
+            seed,
+            counter: 0,
+            block: [0u8; 32],
+            // Mark the current block as used, so that the first read will create a new block:
+            block_pos: 32,
+        }
+    }
+
+    /// Calculate the next block of the stream.
+    fn next_block(&mut self) {
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        ctx.update(&self.seed);
+        ctx.update(&self.counter.to_be_bytes());
+        self.block.copy_from_slice(ctx.finish().as_ref());
+        self.counter += 1;
+        self.block_pos = 0;
+    }
+}
+
+impl Rng for HashRng {
+    fn next_u32(&mut self) -> u32 {
+        if self.block_pos + 4 > self.block.len() {
+            self.next_block();
+        }
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&self.block[self.block_pos .. self.block_pos + 4]);
+        self.block_pos += 4;
+        u32::from_be_bytes(bytes)
+    }
+}
+
+/// Map key to a coordinate. The coordinate has the same distribution as coordinates
+/// generated by the given randomize strategy, and it is the same on every node that
+/// knows the landmarks coordinates.
+pub fn hash_to_coord(key: &[u8], strategy: RandomizeStrategy, upper_constraints: &Vec<u64>,
+                     landmarks: &Vec<usize>, coords: &Vec<Vec<u64>>) -> Vec<u64> {
+    let mut hash_rng = HashRng::new(key);
+    randomize_coord_by_strategy(strategy, upper_constraints, landmarks, coords, &mut hash_rng)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use self::rand::{StdRng};
+    use network_gen::{gen_network};
+    use landmarks::coords::{build_coords, choose_landmarks};
+    use landmarks::coord_mappers::{max_dist};
+    use landmarks::randomize_coord::{calc_upper_constraints};
+
+    #[test]
+    fn test_hash_rng_deterministic() {
+        let mut rng1 = HashRng::new(b"key");
+        let mut rng2 = HashRng::new(b"key");
+        let mut rng3 = HashRng::new(b"other key");
+        let stream1 = (0 .. 20).map(|_| rng1.next_u32()).collect::<Vec<_>>();
+        let stream2 = (0 .. 20).map(|_| rng2.next_u32()).collect::<Vec<_>>();
+        let stream3 = (0 .. 20).map(|_| rng3.next_u32()).collect::<Vec<_>>();
+        assert!(stream1 == stream2);
+        assert!(stream1 != stream3);
+        // First word is the beginning of SHA256(SHA256("key") || 0):
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        ctx.update(digest::digest(&digest::SHA256, b"key").as_ref());
+        ctx.update(&[0u8; 8]);
+        let block = ctx.finish();
+        assert!(stream1[0].to_be_bytes() == block.as_ref()[0 .. 4]);
+    }
+
+    #[test]
+    fn test_hash_to_coord_balanced() {
+        let seed: &[_] = &[1,2,3,4,9];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let g = 7;
+        let net = gen_network(0, g, 2 * g + 1, 0x10000, 0x20000, &mut rng);
+        let landmarks = choose_landmarks(&net, g * g, &mut rng);
+        let coords = build_coords(&net, &landmarks).unwrap();
+        let upper_constraints = calc_upper_constraints(&landmarks, &coords);
+        let num_nodes = net.igraph.node_count();
+
+        // Count how many times every node is the closest node to a key's coordinate:
+        let mut node_hits = vec![0; num_nodes];
+        for key_num in 0 .. num_nodes * 4 {
+            let key = format!("key{}", key_num).into_bytes();
+            let hcoord = hash_to_coord(&key, RandomizeStrategy::RwMix, 
+                                       &upper_constraints, &landmarks, &coords);
+            assert!(hcoord == hash_to_coord(&key, RandomizeStrategy::RwMix,
+                                            &upper_constraints, &landmarks, &coords));
+            let closest_node = (0 .. num_nodes)
+                .min_by_key(|&i| (max_dist(&hcoord, &coords[i]), i))
+                .unwrap();
+            node_hits[closest_node] += 1;
+        }
+
+        // Keys are spread over many nodes, and no node gets a large share of the keys:
+        let num_hit_nodes = node_hits.iter().filter(|&&hits| hits > 0).count();
+        let max_hits = *node_hits.iter().max().unwrap();
+        assert!(num_hit_nodes >= num_nodes / 4);
+        assert!(max_hits <= num_nodes / 4);
+    }
+}
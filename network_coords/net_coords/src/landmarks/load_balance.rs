@@ -0,0 +1,99 @@
+/// Load statistics: How many times every node was hit, and how balanced the hits are.
+#[derive(Clone, Debug)]
+pub struct LoadReport {
+    /// Amount of hits for every node.
+    pub node_hits: Vec<usize>,
+    /// Maximum hits of a node divided by the mean amount of hits.
+    pub max_mean_ratio: f64,
+    /// Gini coefficient of the hits. 0 means perfectly balanced.
+    pub gini: f64,
+    /// Fraction of nodes that were never hit.
+    pub never_hit_ratio: f64,
+}
+
+/// Calculate the Gini coefficient of a list of values.
+/// Returns 0 for an empty list or if all values are 0.
+pub fn gini(values: &[usize]) -> f64 {
+    let n = values.len();
+    let sum: usize = values.iter().sum();
+    if n == 0 || sum == 0 {
+        return 0.0;
+    }
+    let mut sorted_values = values.to_vec();
+    sorted_values.sort();
+
+    // G = 2 * sum_i (i * x_i) / (n * sum) - (n + 1) / n, with i starting from 1.
+    let weighted_sum: f64 = sorted_values.iter().enumerate()
+        .map(|(i, &x)| ((i + 1) as f64) * (x as f64))
+        .sum();
+    (2.0 * weighted_sum) / ((n as f64) * (sum as f64)) - ((n + 1) as f64) / (n as f64)
+}
+
+impl LoadReport {
+    pub fn new(node_hits: Vec<usize>) -> LoadReport {
+        assert!(!node_hits.is_empty(), "No nodes to report about!");
+        let num_nodes = node_hits.len() as f64;
+        let sum_hits: usize = node_hits.iter().sum();
+        let max_hits = *node_hits.iter().max().unwrap();
+        let mean_hits = (sum_hits as f64) / num_nodes;
+        let max_mean_ratio = if sum_hits == 0 {
+            0.0
+        } else {
+            (max_hits as f64) / mean_hits
+        };
+        let num_never_hit = node_hits.iter().filter(|&&hits| hits == 0).count();
+
+        LoadReport {
+            gini: gini(&node_hits),
+            max_mean_ratio,
+            never_hit_ratio: (num_never_hit as f64) / num_nodes,
+            node_hits,
+        }
+    }
+}
+
+/// Randomize num_samples coordinates using randomize, and route to every coordinate
+/// using route. route returns the index of the node where the coordinate landed.
+/// Returns load statistics over the num_nodes nodes of the network.
+pub fn analyze_load<FR, FT>(num_nodes: usize, num_samples: usize,
+                            mut randomize: FR, mut route: FT) -> LoadReport
+    where FR: FnMut() -> Vec<u64>, FT: FnMut(&Vec<u64>) -> usize {
+
+    let mut node_hits = vec![0; num_nodes];
+    for _ in 0 .. num_samples {
+        let rcoord = randomize();
+        node_hits[route(&rcoord)] += 1;
+    }
+    LoadReport::new(node_hits)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gini() {
+        assert!(gini(&[]) == 0.0);
+        assert!(gini(&[0, 0, 0]) == 0.0);
+        assert!(gini(&[5, 5, 5, 5]).abs() < 1e-9);
+        assert!((gini(&[0, 0, 0, 8]) - 0.75).abs() < 1e-9);
+        assert!((gini(&[1, 2, 3]) - 2.0 / 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_analyze_load() {
+        // Randomizer that alternates between two coordinates,
+        // and a router that maps a coordinate to its first entry:
+        let mut counter: u64 = 0;
+        let report = analyze_load(4, 8, || {
+            counter += 1;
+            vec![counter % 2, 0]
+        }, |coord| coord[0] as usize);
+
+        assert!(report.node_hits == vec![4, 4, 0, 0]);
+        assert!((report.max_mean_ratio - 2.0).abs() < 1e-9);
+        assert!((report.never_hit_ratio - 0.5).abs() < 1e-9);
+        assert!((report.gini - 0.5).abs() < 1e-9);
+    }
+}
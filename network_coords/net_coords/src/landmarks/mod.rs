@@ -1,6 +1,14 @@
 pub mod coords;
 pub mod randomize_coord;
 pub mod coord_mappers;
+pub mod storage;
+pub mod hash_coord;
+pub mod load_balance;
+pub mod valleys;
+pub mod drift;
+pub mod codec;
+pub mod bourgain;
+pub mod distortion;
 
 extern crate rand;
 
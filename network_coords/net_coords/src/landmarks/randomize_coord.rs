@@ -507,6 +507,57 @@ pub fn randomize_coord_rw_mix<R: Rng>(upper_constraints: &Vec<u64>,
 
 //////////////////////////////////////////
 
+/// A method to randomize a coordinate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RandomizeStrategy {
+    RandCoeffs,
+    LandmarksCoords,
+    FairCuts,
+    Rw,
+    RwSparse,
+    RwDirectional,
+    RwMix,
+}
+
+impl RandomizeStrategy {
+    /// Get all strategies, as (name, strategy) pairs.
+    pub fn all() -> Vec<(&'static str, RandomizeStrategy)> {
+        vec![
+            ("rand_coeffs", RandomizeStrategy::RandCoeffs),
+            ("landmarks", RandomizeStrategy::LandmarksCoords),
+            ("fair_cuts", RandomizeStrategy::FairCuts),
+            ("rw", RandomizeStrategy::Rw),
+            ("rw_sparse", RandomizeStrategy::RwSparse),
+            ("rw_directional", RandomizeStrategy::RwDirectional),
+            ("rw_mix", RandomizeStrategy::RwMix),
+        ]
+    }
+}
+
+/// Generate a random coordinate using the given strategy.
+pub fn randomize_coord_by_strategy<R: Rng>(strategy: RandomizeStrategy, 
+                  upper_constraints: &Vec<u64>, landmarks: &Vec<usize>, 
+                  coords: &Vec<Vec<u64>>, rng: &mut R) -> Vec<u64> {
+    match strategy {
+        RandomizeStrategy::RandCoeffs => 
+            randomize_coord_rand_coeffs(landmarks, coords, rng),
+        RandomizeStrategy::LandmarksCoords => 
+            randomize_coord_landmarks_coords(landmarks, coords, rng),
+        RandomizeStrategy::FairCuts => 
+            randomize_coord_fair_cuts(landmarks, coords, rng),
+        RandomizeStrategy::Rw => 
+            randomize_coord_rw(upper_constraints, landmarks, coords, rng),
+        RandomizeStrategy::RwSparse => 
+            randomize_coord_rw_sparse(upper_constraints, landmarks, coords, rng),
+        RandomizeStrategy::RwDirectional => 
+            randomize_coord_rw_directional(upper_constraints, landmarks, coords, rng),
+        RandomizeStrategy::RwMix => 
+            randomize_coord_rw_mix(upper_constraints, landmarks, coords, rng),
+    }
+}
+
+//////////////////////////////////////////
+
 /// Slightly change a coordinate (randomly), leaving all entries >= 0
 pub fn drift_coordinate<R: Rng>(noise_size: u64, coord: &mut Vec<u64>, rng: &mut R) {
     let drift_range : Range<i64> = Range::new(-(noise_size as i64), noise_size as i64);
@@ -0,0 +1,277 @@
+extern crate rand;
+
+use std::hash::Hash;
+use std::collections::{HashSet, HashMap};
+
+use self::rand::{Rng};
+
+use network::{Network};
+use landmarks::{KnownNode, find_path_landmarks_areas_by_coord,
+    find_path_landmarks_areas_approx};
+use landmarks::coord_mappers::{max_dist};
+use landmarks::randomize_coord::{randomize_coord_rw_mix, calc_upper_constraints,
+    RandomizeStrategy};
+use landmarks::hash_coord::{hash_to_coord};
+
+/// Map a key to a coordinate. Every node that knows the landmarks coordinates
+/// gets the same coordinate for the same key.
+/// The coordinate is distributed like randomize_coord_rw_mix.
+pub fn key_to_coord(key: &[u8], upper_constraints: &Vec<u64>,
+                    landmarks: &Vec<usize>, coords: &Vec<Vec<u64>>) -> Vec<u64> {
+    hash_to_coord(key, RandomizeStrategy::RwMix, upper_constraints, landmarks, coords)
+}
+
+/// Determines how hard we try to find a coordinate.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum visits to a single node while routing to a coordinate.
+    pub max_visits: usize,
+    /// Maximum path length of a single attempt to find a stored key.
+    /// Path lengths are measured by the distances inside the areas (Hops for gen_areas).
+    pub max_path_len: u64,
+    /// Maximum amount of attempts to find a stored key.
+    /// Every attempt except the first starts from a random coordinate in the network.
+    pub max_attempts: usize,
+}
+
+/// Result of storing a key.
+#[derive(Clone, Debug)]
+pub struct StoreResult {
+    /// Indices of nodes that keep a replica of the key.
+    pub holders: Vec<usize>,
+    /// Length of the path to the node closest to the key's coordinate.
+    pub path_len: u64,
+}
+
+/// Result of looking up a key.
+#[derive(Clone, Debug)]
+pub struct LookupResult {
+    /// Was a node holding the key found?
+    pub found: bool,
+    /// Length of the path of the successful attempt, including its detour to a random
+    /// coordinate.
+    /// Meaningful only if found is true.
+    pub path_len: u64,
+    /// Amount of attempts used.
+    pub attempts: usize,
+}
+
+/// Accumulated statistics over many lookups.
+#[derive(Clone, Debug, Default)]
+pub struct LookupStats {
+    pub num_lookups: usize,
+    pub num_found: usize,
+    /// Sum of path lengths of successful lookups.
+    pub sum_path_len: u64,
+    pub sum_attempts: usize,
+}
+
+impl LookupStats {
+    pub fn add(&mut self, lookup_result: &LookupResult) {
+        self.num_lookups += 1;
+        self.sum_attempts += lookup_result.attempts;
+        if lookup_result.found {
+            self.num_found += 1;
+            self.sum_path_len += lookup_result.path_len;
+        }
+    }
+
+    pub fn success_ratio(&self) -> f64 {
+        (self.num_found as f64) / (self.num_lookups as f64)
+    }
+
+    pub fn avg_path_len(&self) -> f64 {
+        (self.sum_path_len as f64) / (self.num_found as f64)
+    }
+
+    pub fn avg_attempts(&self) -> f64 {
+        (self.sum_attempts as f64) / (self.num_lookups as f64)
+    }
+}
+
+/// A key value store, where every key is kept near a coordinate derived from the key.
+/// Nodes meet at a key by independently routing toward the same coordinate.
+pub struct CoordStorage<'a, Node: 'a> {
+    net: &'a Network<Node>,
+    coords: &'a Vec<Vec<u64>>,
+    landmarks: &'a Vec<usize>,
+    areas: &'a Vec<Vec<KnownNode>>,
+    upper_constraints: Vec<u64>,
+    retry_policy: RetryPolicy,
+    num_replicas: usize,
+    /// key -> indices of nodes holding the key.
+    holders: HashMap<Vec<u8>, HashSet<usize>>,
+}
+
+impl<'a, Node: Hash + Eq + Clone> CoordStorage<'a, Node> {
+    pub fn new(net: &'a Network<Node>, coords: &'a Vec<Vec<u64>>, landmarks: &'a Vec<usize>,
+               areas: &'a Vec<Vec<KnownNode>>, num_replicas: usize,
+               retry_policy: RetryPolicy) -> CoordStorage<'a, Node> {
+
+        assert!(num_replicas > 0, "At least one replica is required!");
+        CoordStorage {
+            net,
+            coords,
+            landmarks,
+            areas,
+            upper_constraints: calc_upper_constraints(landmarks, coords),
+            retry_policy,
+            num_replicas,
+            holders: HashMap::new(),
+        }
+    }
+
+    /// Get the coordinate where key is kept.
+    pub fn key_coord(&self, key: &[u8]) -> Vec<u64> {
+        key_to_coord(key, &self.upper_constraints, self.landmarks, self.coords)
+    }
+
+    /// Store key, starting from src_node.
+    /// We route to the valley node closest to the key's coordinate. This node keeps the key,
+    /// together with the closest nodes to the coordinate out of its area.
+    pub fn store<R: Rng>(&mut self, src_node: usize, key: &[u8], rng: &mut R) -> StoreResult {
+        let key_coord = self.key_coord(key);
+        let (found_node, path_len, _) =
+            find_path_landmarks_areas_by_coord(src_node, &key_coord,
+                       self.retry_policy.max_visits, self.net,
+                       self.coords, self.landmarks, self.areas, rng);
+
+        // Replicate to the nodes closest to the coordinate inside found_node's area:
+        let mut candidates = self.areas[found_node].iter()
+            .map(|known| known.index)
+            .collect::<Vec<usize>>();
+        candidates.push(found_node);
+        candidates.sort_by_key(|&i| (i != found_node, max_dist(&self.coords[i], &key_coord), i));
+        candidates.dedup();
+        candidates.truncate(self.num_replicas);
+
+        self.holders.entry(key.to_vec())
+            .or_default()
+            .extend(candidates.iter().cloned());
+
+        StoreResult {
+            holders: candidates,
+            path_len,
+        }
+    }
+
+    /// Look for key, starting from src_node.
+    /// The first attempt routes directly toward the key's coordinate. Every further attempt
+    /// first routes to a random coordinate, and from there toward the key's coordinate.
+    pub fn lookup<R: Rng>(&self, src_node: usize, key: &[u8], rng: &mut R) -> LookupResult {
+        let empty_holders = HashSet::new();
+        let key_holders = self.holders.get(key).unwrap_or(&empty_holders);
+        let key_coord = self.key_coord(key);
+
+        for attempt in 1 .. self.retry_policy.max_attempts + 1 {
+            let mut start_node = src_node;
+            let mut path_len: u64 = 0;
+            if attempt > 1 {
+                // First go to a random place in the network:
+                let rcoord = randomize_coord_rw_mix(&self.upper_constraints,
+                                                    self.landmarks, self.coords, rng);
+                let (rnode, first_part_len, _) =
+                    find_path_landmarks_areas_by_coord(src_node, &rcoord,
+                               self.retry_policy.max_visits, self.net,
+                               self.coords, self.landmarks, self.areas, rng);
+                start_node = rnode;
+                path_len = first_part_len;
+            }
+
+            if key_holders.contains(&start_node) {
+                return LookupResult {
+                    found: true,
+                    path_len,
+                    attempts: attempt,
+                };
+            }
+
+            // Starting from start_node, try to find the key's coordinate:
+            let opt_path_len = find_path_landmarks_areas_approx(start_node, key_holders,
+                               &key_coord, self.retry_policy.max_path_len, self.net,
+                               self.coords, self.landmarks, self.areas, rng);
+
+            if let Some(attempt_path_len) = opt_path_len {
+                return LookupResult {
+                    found: true,
+                    path_len: path_len + attempt_path_len,
+                    attempts: attempt,
+                };
+            }
+        }
+
+        LookupResult {
+            found: false,
+            path_len: 0,
+            attempts: self.retry_policy.max_attempts,
+        }
+    }
+
+    /// Amount of keys kept by every node.
+    pub fn node_load(&self) -> Vec<usize> {
+        let mut load = vec![0; self.net.igraph.node_count()];
+        for key_holders in self.holders.values() {
+            for &i in key_holders {
+                load[i] += 1;
+            }
+        }
+        load
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use self::rand::{StdRng, SeedableRng};
+    use network_gen::{gen_network};
+    use landmarks::{gen_areas};
+    use landmarks::coords::{build_coords, choose_landmarks};
+
+    #[test]
+    fn test_coord_storage() {
+        let seed: &[_] = &[1,2,3,4,9];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let g = 6;
+        let net = gen_network(0, g, 2 * g + 1, 0x10000, 0x20000, &mut rng);
+        let amount_close = g * g;
+        let areas = gen_areas(amount_close, &net);
+        let landmarks = choose_landmarks(&net, g * g, &mut rng);
+        let coords = build_coords(&net, &landmarks).unwrap();
+
+        let retry_policy = RetryPolicy {
+            max_visits: 2,
+            max_path_len: net.igraph.node_count() as u64,
+            max_attempts: g,
+        };
+        let mut storage = CoordStorage::new(&net, &coords, &landmarks, &areas,
+                                            3, retry_policy);
+
+        // Key to coordinate mapping is deterministic:
+        assert!(storage.key_coord(b"hello") == storage.key_coord(b"hello"));
+
+        let node_count = net.igraph.node_count();
+        let mut lookup_stats = LookupStats::default();
+        for key_num in 0 .. 20_usize {
+            let key = format!("key{}", key_num).into_bytes();
+            let store_result = storage.store(key_num % node_count, &key, &mut rng);
+            assert!(store_result.holders.len() == 3);
+
+            // Looking up from a holder is immediate:
+            let lookup_result = storage.lookup(store_result.holders[0], &key, &mut rng);
+            assert!(lookup_result.found);
+            assert!(lookup_result.path_len == 0);
+            assert!(lookup_result.attempts == 1);
+
+            let lookup_result = storage.lookup((key_num * 7 + 3) % node_count, &key, &mut rng);
+            lookup_stats.add(&lookup_result);
+        }
+        assert!(lookup_stats.num_lookups == 20);
+        assert!(lookup_stats.success_ratio() > 0.5);
+        assert!(storage.node_load().iter().sum::<usize>() == 20 * 3);
+
+        // A key that was never stored is never found:
+        let lookup_result = storage.lookup(0, b"missing", &mut rng);
+        assert!(!lookup_result.found);
+    }
+}
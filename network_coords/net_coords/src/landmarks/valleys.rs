@@ -0,0 +1,224 @@
+use std::hash::Hash;
+
+use network::{Network};
+use landmarks::coord_mappers::{approx_max_dist, max_dist};
+
+/// What it takes for a node to find a node strictly closer to the destination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Escape {
+    /// Distance in hops (Ignoring weights) to the closest strictly better node.
+    pub radius: u64,
+    /// Position of the closest strictly better node in the closest_nodes_structure order,
+    /// starting from 1. This is the minimal area size (amount_close) that lets the node
+    /// escape.
+    pub area: usize,
+}
+
+/// A census of all the greedy local minima (valleys) of a metric over a network,
+/// with respect to one destination.
+#[derive(Clone, Debug)]
+pub struct ValleyCensus {
+    /// Indices of all nodes that have no immediate neighbor strictly closer to the
+    /// destination. Sorted by index.
+    pub valleys: Vec<usize>,
+    /// For every node, the valley it reaches by greedy descent over immediate neighbors.
+    pub basin_of: Vec<usize>,
+    /// For every valley (Same order as valleys), the amount of nodes in its basin.
+    pub basin_sizes: Vec<usize>,
+    /// For every node, what it takes to escape to a strictly better node.
+    /// None if no node in the network is strictly better (A global minimum).
+    pub escape: Vec<Option<Escape>>,
+}
+
+impl ValleyCensus {
+    /// Valleys that are not global minima. Routing can get stuck at those.
+    pub fn false_valleys(&self) -> Vec<usize> {
+        self.valleys.iter()
+            .cloned()
+            .filter(|&v| self.escape[v].is_some())
+            .collect()
+    }
+
+    /// Minimal area size (amount_close) that allows every node to find a strictly
+    /// better node. 0 if no node can find a strictly better node.
+    pub fn max_escape_area(&self) -> usize {
+        self.escape.iter()
+            .filter_map(|opt_escape| opt_escape.map(|escape| escape.area))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Maximal escape radius (In hops) over all nodes.
+    /// 0 if no node needs to escape.
+    pub fn max_escape_radius(&self) -> u64 {
+        self.escape.iter()
+            .filter_map(|opt_escape| opt_escape.map(|escape| escape.radius))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Fraction of nodes that greedily descend into a false valley.
+    pub fn trapped_ratio(&self) -> f64 {
+        let num_trapped = self.basin_of.iter()
+            .filter(|&&v| self.escape[v].is_some())
+            .count();
+        (num_trapped as f64) / (self.basin_of.len() as f64)
+    }
+}
+
+/// Get the neighbor of node_index that is strictly closer to the destination than
+/// node_index, and closest to the destination among its neighbors.
+/// Ties are broken by the lowest index.
+fn greedy_step<Node, F>(node_index: usize, net: &Network<Node>, node_dist: &F) -> Option<usize>
+    where F: Fn(usize) -> u64 {
+
+    let (best_dist, best_index) = net.igraph.neighbors(node_index)
+        .map(|nei_index| (node_dist(nei_index), nei_index))
+        .min()?;
+
+    if best_dist < node_dist(node_index) {
+        Some(best_index)
+    } else {
+        None
+    }
+}
+
+/// Find the first node in the closest_nodes_structure order of node_index that
+/// is strictly closer to the destination.
+fn find_escape<Node: Hash + Eq + Clone, F>(node_index: usize, net: &Network<Node>, node_dist: &F)
+    -> Option<Escape> where F: Fn(usize) -> u64 {
+
+    let cur_dist = node_dist(node_index);
+    net.closest_nodes_structure(node_index)
+        .enumerate()
+        .find(|&(_, (i, _, _))| node_dist(i) < cur_dist)
+        .map(|(pos, (_, radius, _))| Escape {
+            radius,
+            area: pos + 1,
+        })
+}
+
+/// Calculate the valley census of a network for a general metric.
+/// node_dist(i) is the distance of the node with index i from the destination.
+pub fn valley_census<Node: Hash + Eq + Clone, F>(net: &Network<Node>, node_dist: F)
+    -> ValleyCensus where F: Fn(usize) -> u64 {
+
+    let num_nodes = net.igraph.node_count();
+    let next_step: Vec<Option<usize>> = (0 .. num_nodes)
+        .map(|i| greedy_step(i, net, &node_dist))
+        .collect();
+
+    let valleys: Vec<usize> = (0 .. num_nodes)
+        .filter(|&i| next_step[i].is_none())
+        .collect();
+
+    // Every greedy step strictly decreases the distance, so following the steps
+    // always ends at a valley:
+    let mut basin_of: Vec<Option<usize>> = vec![None; num_nodes];
+    for i in 0 .. num_nodes {
+        let mut path = Vec::new();
+        let mut cur_node = i;
+        let valley = loop {
+            if let Some(valley) = basin_of[cur_node] {
+                break valley;
+            }
+            path.push(cur_node);
+            match next_step[cur_node] {
+                Some(next_node) => cur_node = next_node,
+                None => break cur_node,
+            }
+        };
+        for node_index in path {
+            basin_of[node_index] = Some(valley);
+        }
+    }
+    let basin_of: Vec<usize> = basin_of.into_iter()
+        .map(|opt_valley| opt_valley.unwrap())
+        .collect();
+
+    let mut basin_sizes = vec![0; valleys.len()];
+    for &valley in &basin_of {
+        // valleys is sorted by index:
+        basin_sizes[valleys.binary_search(&valley).unwrap()] += 1;
+    }
+
+    let escape = (0 .. num_nodes)
+        .map(|i| find_escape(i, net, &node_dist))
+        .collect();
+
+    ValleyCensus {
+        valleys,
+        basin_of,
+        basin_sizes,
+        escape,
+    }
+}
+
+/// Calculate the valley census for routing toward a destination coordinate.
+pub fn valley_census_by_coord<Node: Hash + Eq + Clone>(net: &Network<Node>,
+        coords: &Vec<Vec<u64>>, dst_coord: &Vec<u64>) -> ValleyCensus {
+
+    valley_census(net, |i| max_dist(&coords[i], dst_coord))
+}
+
+/// Calculate the valley census for routing toward a destination node.
+pub fn valley_census_by_node<Node: Hash + Eq + Clone>(net: &Network<Node>,
+        coords: &Vec<Vec<u64>>, landmarks: &Vec<usize>, dst_node: usize) -> ValleyCensus {
+
+    valley_census(net, |i| approx_max_dist(i, dst_node, coords, landmarks))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use network::{grid2_net};
+
+    #[test]
+    fn test_valley_census_exact_metric() {
+        // Using the exact distance on a grid, the destination is the only valley:
+        let k = 5;
+        let net = grid2_net(k);
+        let dst_node = 7;
+        let census = valley_census(&net, |i| {
+            let (x, y) = (i / k, i % k);
+            let (dx, dy) = (dst_node / k, dst_node % k);
+            ((x as i64 - dx as i64).abs() + (y as i64 - dy as i64).abs()) as u64
+        });
+
+        assert!(census.valleys == vec![dst_node]);
+        assert!(census.basin_sizes == vec![k * k]);
+        assert!(census.basin_of.iter().all(|&v| v == dst_node));
+        assert!(census.escape[dst_node].is_none());
+        for i in (0 .. k * k).filter(|&i| i != dst_node) {
+            assert!(census.escape[i].unwrap().radius == 1);
+        }
+        assert!(census.false_valleys().is_empty());
+        assert!(census.trapped_ratio() == 0.0);
+    }
+
+    #[test]
+    fn test_valley_census_false_valley() {
+        // A path 0 - 1 - 2 - 3 - 4, where node 1 is a false valley:
+        let mut net = Network::<usize>::new();
+        for v in 0 .. 5 {
+            net.add_node(v);
+        }
+        for v in 0 .. 4 {
+            net.igraph.add_edge(v, v + 1, 1);
+        }
+        let dists = [3, 2, 5, 4, 0];
+        let census = valley_census(&net, |i| dists[i]);
+
+        assert!(census.valleys == vec![1, 4]);
+        assert!(census.basin_of == vec![1, 1, 1, 4, 4]);
+        assert!(census.basin_sizes == vec![3, 2]);
+        assert!(census.false_valleys() == vec![1]);
+        // Node 1 has to reach node 4, which is 3 hops away:
+        assert!(census.escape[1] == Some(Escape { radius: 3, area: 4 }));
+        assert!(census.escape[4].is_none());
+        assert!(census.max_escape_area() == 4);
+        assert!(census.max_escape_radius() == 3);
+        assert!((census.trapped_ratio() - 0.6).abs() < 1e-9);
+    }
+}
@@ -7,3 +7,7 @@ pub mod statistic;
 pub mod index_pair;
 pub mod chord;
 pub mod towers;
+pub mod signed_chain;
+pub mod tower_resolve;
+pub mod tower_incentive;
+pub mod vivaldi;
@@ -3,6 +3,7 @@ use self::rand::{Rng};
 
 use network::{Network};
 use chord::{RingKey};
+use chord::ring_key::{RingKeyOps};
 use smallest_k::{SmallestK};
 use std::collections::{HashSet, HashMap};
 use self::rand::distributions::{IndependentSample, Range};
@@ -243,7 +244,8 @@ pub fn random_weighted_net_planar<R: Rng>(num_nodes: usize, num_cons: usize,
     let planar_dist = |i: usize, j:usize| {
         let (a,b) = index_to_coord.get(&i).unwrap().clone();
         let (c,d) = index_to_coord.get(&j).unwrap().clone();
-        (c - a).pow(2) + (d - b).pow(2)
+        // Use absolute differences to avoid unsigned underflow:
+        (c.max(a) - c.min(a)).pow(2) + (d.max(b) - d.min(b)).pow(2)
     };
 
     for u in 0 .. num_nodes {
@@ -370,6 +372,35 @@ pub fn gen_network<R:Rng>(net_type: usize, g: usize,l: usize,
 }
 
 
+/// Copy the topology of a network, assigning new random keys of type K in [0, 2^l).
+/// Useful for running chord over wide keys (128 or 256 bits), where
+/// the keyspace is much larger than 2^64.
+/// Node indices are kept the same.
+pub fn rekey_network<K: RingKeyOps, Node, R: Rng>(net: &Network<Node>, l: usize, 
+                                                  rng: &mut R) -> Network<K> {
+    assert!(l <= K::num_bits(), "Key space is too large for the key type!");
+    let num_nodes = net.igraph.node_count();
+    // We stay below sqrt(keyspace_size), to avoid collisions.
+    assert!(((num_nodes as f64).log2() as usize) < l / 2, "Too many nodes!");
+
+    let mut new_net = Network::<K>::new();
+    let mut chosen_keys: HashSet<K> = HashSet::new();
+    for _ in 0 .. num_nodes {
+        let mut node_key = K::rand_key(l, rng);
+        while chosen_keys.contains(&node_key) {
+            node_key = K::rand_key(l, rng);
+        }
+        chosen_keys.insert(node_key);
+        new_net.add_node(node_key);
+    }
+
+    for (a, b, &weight) in net.igraph.all_edges() {
+        new_net.igraph.add_edge(a, b, weight);
+    }
+    new_net
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,198 @@
+extern crate rand;
+extern crate ring;
+
+use self::rand::{Rng};
+use self::ring::signature::{self, Ed25519KeyPair, KeyPair};
+
+/// Size of an Ed25519 public key, in bytes.
+pub const PUBLIC_KEY_LEN: usize = 32;
+/// Size of an Ed25519 signature, in bytes.
+pub const SIGNATURE_LEN: usize = 64;
+
+/// Generate a random Ed25519 key pair.
+pub fn gen_key_pair<R: Rng>(rng: &mut R) -> Ed25519KeyPair {
+    let mut seed = [0u8; 32];
+    rng.fill_bytes(&mut seed);
+    Ed25519KeyPair::from_seed_unchecked(&seed).unwrap()
+}
+
+pub fn public_key_bytes(key_pair: &Ed25519KeyPair) -> Vec<u8> {
+    key_pair.public_key().as_ref().to_vec()
+}
+
+/// One hop of a signed chain: The next node on the path, and the signature of the
+/// previous node over (previous line, next node).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainLink {
+    /// Public key of the next node.
+    pub node: Vec<u8>,
+    /// Signature of the previous node.
+    pub signature: Vec<u8>,
+}
+
+/// A chain of signatures proving an upper bound on the length of a path from a tower
+/// to the last node of the chain:
+///
+/// ```text
+/// cur_time
+/// a1, sign_[a0](prev, a1)
+/// a2, sign_[a1](prev, a2)
+/// ...
+/// ```
+///
+/// Intermediate nodes may make the chain longer, but they can not make it shorter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedChain {
+    /// Public key of the tower (a0).
+    tower: Vec<u8>,
+    /// The time in which the tower has started the chain.
+    timestamp: u64,
+    links: Vec<ChainLink>,
+}
+
+impl SignedChain {
+    /// Start a new chain at a tower.
+    pub fn new(tower_public_key: &[u8], timestamp: u64) -> SignedChain {
+        SignedChain {
+            tower: tower_public_key.to_vec(),
+            timestamp,
+            links: Vec::new(),
+        }
+    }
+
+    /// The message signed when adding link number index: (previous line, next node).
+    fn link_msg(&self, index: usize, next_public_key: &[u8]) -> Vec<u8> {
+        let mut msg = if index == 0 {
+            self.timestamp.to_be_bytes().to_vec()
+        } else {
+            let prev_link = &self.links[index - 1];
+            let mut line = prev_link.node.clone();
+            line.extend_from_slice(&prev_link.signature);
+            line
+        };
+        msg.extend_from_slice(next_public_key);
+        msg
+    }
+
+    /// Extend the chain by one hop to next_public_key.
+    /// key_pair must be the key pair of the last node of the chain.
+    pub fn extend(&self, key_pair: &Ed25519KeyPair, next_public_key: &[u8]) -> SignedChain {
+        assert!(key_pair.public_key().as_ref() == self.last_node(),
+                "Only the last node may extend the chain!");
+        let msg = self.link_msg(self.links.len(), next_public_key);
+        let mut chain = self.clone();
+        chain.links.push(ChainLink {
+            node: next_public_key.to_vec(),
+            signature: key_pair.sign(&msg).as_ref().to_vec(),
+        });
+        chain
+    }
+
+    /// Amount of hops in the chain. An upper bound on the length of a path from the tower
+    /// to the last node.
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    pub fn tower(&self) -> &[u8] {
+        &self.tower
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Public key of the last node of the chain.
+    pub fn last_node(&self) -> &[u8] {
+        match self.links.last() {
+            None => &self.tower,
+            Some(link) => &link.node,
+        }
+    }
+
+    /// Public keys of all nodes of the chain, starting from the tower.
+    pub fn nodes(&self) -> Vec<&[u8]> {
+        let mut nodes = vec![&self.tower[..]];
+        nodes.extend(self.links.iter().map(|link| &link.node[..]));
+        nodes
+    }
+
+    /// Size of the chain on the wire, in bytes.
+    pub fn wire_size(&self) -> usize {
+        PUBLIC_KEY_LEN + 8 + self.links.len() * (PUBLIC_KEY_LEN + SIGNATURE_LEN)
+    }
+
+    /// Verify all signatures of the chain.
+    pub fn verify_signatures(&self) -> bool {
+        let nodes = self.nodes();
+        self.links.iter()
+            .enumerate()
+            .all(|(index, link)| {
+                let public_key = signature::UnparsedPublicKey::new(
+                    &signature::ED25519, nodes[index]);
+                public_key.verify(&self.link_msg(index, &link.node), &link.signature).is_ok()
+            })
+    }
+
+    /// Check that the chain was started at most max_age time units before now.
+    pub fn is_fresh(&self, now: u64, max_age: u64) -> bool {
+        self.timestamp <= now && now - self.timestamp <= max_age
+    }
+
+    /// Check that the chain is fresh and all of its signatures are valid.
+    pub fn verify(&self, now: u64, max_age: u64) -> bool {
+        self.is_fresh(now, max_age) && self.verify_signatures()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use self::rand::{StdRng, SeedableRng};
+
+    #[test]
+    fn test_signed_chain() {
+        let seed: &[_] = &[1,2,3,4,15];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let key_pairs = (0 .. 6)
+            .map(|_| gen_key_pair(&mut rng))
+            .collect::<Vec<Ed25519KeyPair>>();
+        let public_key = |i: usize| public_key_bytes(&key_pairs[i]);
+
+        // a0 --> a1 --> ... --> a5
+        let mut chain = SignedChain::new(&public_key(0), 100);
+        assert!(chain.is_empty());
+        for i in 1 .. 6 {
+            chain = chain.extend(&key_pairs[i - 1], &public_key(i));
+        }
+        assert!(chain.len() == 5);
+        assert!(chain.last_node() == &public_key(5)[..]);
+        assert!(chain.nodes().len() == 6);
+        assert!(chain.nodes()[0] == &public_key(0)[..]);
+        assert!(chain.wire_size() == 40 + 5 * 96);
+        assert!(chain.verify(110, 10));
+        // Too old, or from the future:
+        assert!(!chain.verify(111, 10));
+        assert!(!chain.verify(99, 10));
+
+        // A node in the middle can not remove a hop:
+        let mut shortened = chain.clone();
+        shortened.links.remove(2);
+        assert!(!shortened.verify_signatures());
+
+        // Changing the timestamp breaks the first signature:
+        let mut refreshed = chain.clone();
+        refreshed.timestamp = 105;
+        assert!(!refreshed.verify_signatures());
+
+        // A chain claimed to start at a different tower is invalid:
+        let mut other_tower = chain.clone();
+        other_tower.tower = public_key(5);
+        assert!(!other_tower.verify_signatures());
+    }
+}
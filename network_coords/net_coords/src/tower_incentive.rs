@@ -0,0 +1,388 @@
+extern crate rand;
+
+use std::hash::Hash;
+use std::collections::{HashSet, HashMap};
+
+use self::rand::{Rng};
+
+use network::{Network};
+use random_util::{choose_k_nums};
+use towers::{LocalTowerInfo, TowersOverlay, calc_indexers_info};
+
+/// Nodes that volunteered to be towers, but drop some of the traffic they should forward.
+pub struct FreeRiders {
+    pub free_riders: HashSet<usize>,
+    /// Probability of a free rider to drop a message it should forward.
+    pub drop_prob: f64,
+}
+
+impl FreeRiders {
+    /// Randomly choose a fraction of the chosen towers to be free riders.
+    pub fn new<R: Rng>(chosen_towers: &Vec<Vec<usize>>, fraction: f64, drop_prob: f64,
+                       rng: &mut R) -> FreeRiders {
+        assert!((0.0 .. 1.0).contains(&fraction), "Invalid free riders fraction!");
+        let mut towers = chosen_towers.iter().flatten().cloned().collect::<Vec<usize>>();
+        towers.sort();
+        towers.dedup();
+        let num_free_riders = ((towers.len() as f64) * fraction) as usize;
+        FreeRiders {
+            free_riders: choose_k_nums(num_free_riders, towers.len(), rng)
+                .into_iter()
+                .map(|tower_index| towers[tower_index])
+                .collect(),
+            drop_prob,
+        }
+    }
+
+    pub fn is_free_rider(&self, node: usize) -> bool {
+        self.free_riders.contains(&node)
+    }
+
+    /// Check if a tower forwards a message.
+    fn forwards<R: Rng>(&self, tower_node: usize, rng: &mut R) -> bool {
+        !self.is_free_rider(tower_node) || rng.gen::<f64>() >= self.drop_prob
+    }
+}
+
+/// Reliability of towers, as observed by every node.
+/// Every node keeps a reliability estimate for every tower it has sent messages through: A
+/// moving average of whether the tower forwarded the messages. A node uses the closest of its
+/// local towers of every color that was reliable enough so far, and sends messages only through
+/// reliable towers in the overlay. A tower that is not used anymore is not measured anymore, so
+/// a node never uses it again, unless all other towers are worse.
+pub struct TowersReliability {
+    /// For every node and color: Closest towers of this color, closest first.
+    candidates: Vec<Vec<Vec<LocalTowerInfo>>>,
+    /// For every node: Estimated reliability of every tower measured so far.
+    /// Towers that were not measured yet are considered fully reliable.
+    reliability: Vec<HashMap<usize, f64>>,
+    /// Weight of a new result in the reliability estimate.
+    learning_rate: f64,
+    /// A node stops using a tower with reliability below this threshold.
+    switch_threshold: f64,
+    /// Amount of times some node has switched to another local tower.
+    num_switches: usize,
+}
+
+impl TowersReliability {
+    pub fn new<Node: Hash + Eq + Clone>(net: &Network<Node>, chosen_towers: &Vec<Vec<usize>>,
+               num_candidates: usize, learning_rate: f64,
+               switch_threshold: f64) -> TowersReliability {
+
+        let num_nodes = net.igraph.node_count();
+        let mut candidates: Vec<Vec<Vec<LocalTowerInfo>>> =
+            (0 .. num_nodes).map(|_| Vec::new()).collect();
+        for ctowers in chosen_towers {
+            let color_info = calc_indexers_info(net, ctowers, num_candidates);
+            for (node, node_info) in color_info.into_iter().enumerate() {
+                candidates[node].push(node_info);
+            }
+        }
+
+        TowersReliability {
+            candidates,
+            reliability: vec![HashMap::new(); num_nodes],
+            learning_rate,
+            switch_threshold,
+            num_switches: 0,
+        }
+    }
+
+    /// Reliability of tower_node, as estimated by node.
+    pub fn reliability(&self, node: usize, tower_node: usize) -> f64 {
+        self.reliability[node].get(&tower_node).cloned().unwrap_or(1.0)
+    }
+
+    /// Does node consider tower_node reliable enough to send messages through it?
+    pub fn is_reliable(&self, node: usize, tower_node: usize) -> bool {
+        self.reliability(node, tower_node) >= self.switch_threshold
+    }
+
+    /// Index of the tower of some color currently used by a node:
+    /// The closest reliable tower, or the most reliable tower if none is reliable.
+    fn preferred_index(&self, node: usize, tower_color: usize) -> usize {
+        let candidates = &self.candidates[node][tower_color];
+        match candidates.iter().position(|info| self.is_reliable(node, info.tower_node())) {
+            Some(index) => index,
+            None => (0 .. candidates.len())
+                .max_by(|&a, &b| {
+                    let reliability_a = self.reliability(node, candidates[a].tower_node());
+                    let reliability_b = self.reliability(node, candidates[b].tower_node());
+                    reliability_a.partial_cmp(&reliability_b).unwrap().then(b.cmp(&a))
+                })
+                .unwrap(),
+        }
+    }
+
+    /// The tower of some color currently used by a node.
+    pub fn preferred_tower(&self, node: usize, tower_color: usize) -> &LocalTowerInfo {
+        &self.candidates[node][tower_color][self.preferred_index(node, tower_color)]
+    }
+
+    /// Update the reliability of tower_node as estimated by node,
+    /// after node has learned whether tower_node forwarded a message.
+    pub fn update(&mut self, node: usize, tower_node: usize, forwarded: bool) {
+        // Only colors where tower_node is a candidate may switch towers:
+        let preferred_before = (0 .. self.candidates[node].len())
+            .filter(|&tower_color| self.candidates[node][tower_color].iter()
+                    .any(|info| info.tower_node() == tower_node))
+            .map(|tower_color| (tower_color, self.preferred_index(node, tower_color)))
+            .collect::<Vec<_>>();
+
+        let result = if forwarded { 1.0 } else { 0.0 };
+        let learning_rate = self.learning_rate;
+        let reliability = self.reliability[node].entry(tower_node).or_insert(1.0);
+        *reliability += learning_rate * (result - *reliability);
+
+        self.num_switches += preferred_before.into_iter()
+            .filter(|&(tower_color, index)| self.preferred_index(node, tower_color) != index)
+            .count();
+    }
+
+    pub fn num_switches(&self) -> usize {
+        self.num_switches
+    }
+
+    /// Count pairs of (node, color) where the node uses a free rider tower of this color.
+    /// Nodes that are towers themselves are not counted.
+    pub fn count_free_rider_preferences(&self, free_riders: &FreeRiders) -> usize {
+        (0 .. self.candidates.len())
+            .map(|node| (0 .. self.candidates[node].len())
+                 .filter(|&tower_color| {
+                     let tower_node = self.preferred_tower(node, tower_color).tower_node();
+                     tower_node != node && free_riders.is_free_rider(tower_node)
+                 }).count())
+            .sum()
+    }
+}
+
+/// Result of sending a message through the towers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Delivery {
+    pub delivered: bool,
+    pub tower_color: usize,
+    /// Towers along the overlay path, from the local tower of the source to the local tower of
+    /// the destination.
+    pub path: Vec<usize>,
+    /// Length of the full path: Climb to the source tower, overlay and descend.
+    pub path_len: u64,
+}
+
+/// Send a message from src_node to dst_node through the towers.
+/// Among the colors where src_node considers the local tower of dst_node reliable, the color
+/// with the shortest climb and descend is used. The message goes through the shortest overlay
+/// path that avoids towers src_node considers unreliable (Or the shortest overlay path, if
+/// there is no such path).
+/// Every tower along the overlay path (Except for src_node and dst_node themselves) may drop the
+/// message. Every tower that forwards the message acknowledges it to src_node, so src_node
+/// learns which tower has dropped the message, and updates the reliability of the towers along
+/// the path.
+/// Returns None if there is no path in the overlay.
+pub fn send_message<R: Rng>(src_node: usize, dst_node: usize,
+                            towers_reliability: &mut TowersReliability,
+                            overlay: &TowersOverlay, free_riders: &FreeRiders,
+                            rng: &mut R) -> Option<Delivery> {
+
+    let num_colors = towers_reliability.candidates[src_node].len();
+    let tower_color = (0 .. num_colors)
+        .min_by_key(|&tower_color| {
+            let src_info = towers_reliability.preferred_tower(src_node, tower_color);
+            let dst_info = towers_reliability.preferred_tower(dst_node, tower_color);
+            (!towers_reliability.is_reliable(src_node, dst_info.tower_node()),
+             src_info.distance() + dst_info.distance(), tower_color)
+        })?;
+
+    let src_info = towers_reliability.preferred_tower(src_node, tower_color).clone();
+    let dst_info = towers_reliability.preferred_tower(dst_node, tower_color).clone();
+    let (overlay_len, path) = {
+        let is_reliable = |tower_node| towers_reliability.is_reliable(src_node, tower_node);
+        overlay.overlay_path_filtered(src_info.tower_node(), dst_info.tower_node(), is_reliable)
+            .or_else(|| overlay.overlay_path(src_info.tower_node(), dst_info.tower_node()))?
+    };
+
+    let mut delivered = true;
+    for &tower_node in &path {
+        if tower_node == src_node || tower_node == dst_node {
+            continue;
+        }
+        let forwarded = free_riders.forwards(tower_node, rng);
+        towers_reliability.update(src_node, tower_node, forwarded);
+        if !forwarded {
+            delivered = false;
+            break;
+        }
+    }
+
+    Some(Delivery {
+        delivered,
+        tower_color,
+        path,
+        path_len: src_info.distance() + overlay_len + dst_info.distance(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use self::rand::{StdRng, SeedableRng};
+    use network_gen::{gen_network};
+    use towers::{choose_towers, calc_towers_info};
+
+    #[test]
+    fn test_towers_reliability() {
+        let seed: &[_] = &[1,2,3,4,17];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let net = gen_network(0, 7, 15, 1, 2, &mut rng);
+        let chosen_towers = choose_towers(&net, 4, 8, &mut rng);
+        let towers_info = calc_towers_info(&net, &chosen_towers);
+
+        let mut towers_reliability = TowersReliability::new(&net, &chosen_towers, 3, 0.5, 0.3);
+        let node = 5;
+        let tower_color = 2;
+        // Initially the closest tower is used:
+        assert!(towers_reliability.preferred_tower(node, tower_color).distance() ==
+                towers_info[node][tower_color].as_ref().unwrap().distance());
+        let candidates = towers_reliability.candidates[node][tower_color].clone();
+        assert!(candidates.len() == 3);
+
+        // Two failures are required to switch to the next tower:
+        towers_reliability.update(node, candidates[0].tower_node(), false);
+        assert!(towers_reliability.preferred_tower(node, tower_color) == &candidates[0]);
+        towers_reliability.update(node, candidates[0].tower_node(), false);
+        assert!(towers_reliability.preferred_tower(node, tower_color) == &candidates[1]);
+        assert!(towers_reliability.num_switches() == 1);
+        // Reliability is estimated separately by every node:
+        assert!(towers_reliability.is_reliable(node + 1, candidates[0].tower_node()));
+
+        towers_reliability.update(node, candidates[1].tower_node(), false);
+        towers_reliability.update(node, candidates[1].tower_node(), false);
+        assert!(towers_reliability.preferred_tower(node, tower_color) == &candidates[2]);
+
+        // All towers are equally unreliable. The closest one is used:
+        towers_reliability.update(node, candidates[2].tower_node(), false);
+        towers_reliability.update(node, candidates[2].tower_node(), false);
+        assert!(towers_reliability.preferred_tower(node, tower_color) == &candidates[0]);
+        // The most reliable tower is used:
+        towers_reliability.update(node, candidates[0].tower_node(), false);
+        assert!(towers_reliability.preferred_tower(node, tower_color) == &candidates[1]);
+        towers_reliability.update(node, candidates[1].tower_node(), true);
+        assert!(towers_reliability.preferred_tower(node, tower_color) == &candidates[1]);
+        assert!(towers_reliability.num_switches() == 4);
+    }
+
+    #[test]
+    fn test_send_message_free_riders() {
+        let seed: &[_] = &[1,2,3,4,18];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let net = gen_network(0, 8, 17, 1, 2, &mut rng);
+        let num_nodes = net.igraph.node_count();
+        let chosen_towers = choose_towers(&net, 4, 8, &mut rng);
+        let towers_info = calc_towers_info(&net, &chosen_towers);
+        let overlay = TowersOverlay::new(&chosen_towers, &towers_info);
+
+        let count_delivered = |towers_reliability: &mut TowersReliability,
+                               free_riders: &FreeRiders, rng: &mut StdRng| {
+            (0 .. num_nodes)
+                .filter(|&i| {
+                    let src_node = i;
+                    let dst_node = (i * 13 + 5) % num_nodes;
+                    let delivery = send_message(src_node, dst_node, towers_reliability,
+                                                &overlay, free_riders, rng).unwrap();
+                    assert!(delivery.path_len >= net.dist(src_node, dst_node).unwrap());
+                    delivery.delivered
+                }).count()
+        };
+
+        // Without free riders all messages are delivered:
+        let honest = FreeRiders::new(&chosen_towers, 0.0, 1.0, &mut rng);
+        let mut towers_reliability = TowersReliability::new(&net, &chosen_towers, 3, 0.5, 0.3);
+        assert!(count_delivered(&mut towers_reliability, &honest, &mut rng) == num_nodes);
+        assert!(towers_reliability.num_switches() == 0);
+
+        // Nodes that never switch towers keep losing messages.
+        // Nodes that switch towers route around the free riders:
+        let free_riders = FreeRiders::new(&chosen_towers, 0.25, 1.0, &mut rng);
+        assert!(free_riders.free_riders.len() ==
+                chosen_towers.iter().flatten().collect::<HashSet<_>>().len() / 4);
+        let mut static_reliability = TowersReliability::new(&net, &chosen_towers, 3, 0.5, 0.0);
+        let mut adaptive_reliability = TowersReliability::new(&net, &chosen_towers, 3, 0.5, 0.3);
+        let bad_before = adaptive_reliability.count_free_rider_preferences(&free_riders);
+        assert!(bad_before > 0);
+
+        let mut static_delivered = 0;
+        let mut adaptive_delivered = 0;
+        for _ in 0 .. 4 {
+            static_delivered = count_delivered(&mut static_reliability, &free_riders, &mut rng);
+            adaptive_delivered = count_delivered(&mut adaptive_reliability, &free_riders,
+                                                 &mut rng);
+        }
+        assert!(static_reliability.num_switches() == 0);
+        assert!(adaptive_reliability.num_switches() > 0);
+        assert!(static_delivered < num_nodes);
+        assert!(adaptive_delivered > static_delivered);
+        assert!(adaptive_reliability.count_free_rider_preferences(&free_riders) < bad_before);
+    }
+
+    #[test]
+    fn test_send_message_middle_free_rider() {
+        let seed: &[_] = &[1,2,3,4,19];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let net = gen_network(0, 8, 17, 1, 2, &mut rng);
+        let num_nodes = net.igraph.node_count();
+        let chosen_towers = choose_towers(&net, 4, 8, &mut rng);
+        let towers_info = calc_towers_info(&net, &chosen_towers);
+        let overlay = TowersOverlay::new(&chosen_towers, &towers_info);
+
+        // Find a message whose overlay path goes through a middle tower that can be avoided:
+        let honest = FreeRiders::new(&chosen_towers, 0.0, 1.0, &mut rng);
+        let mut towers_reliability = TowersReliability::new(&net, &chosen_towers, 3, 0.5, 0.3);
+        let (src_node, dst_node, middle_tower) = (0 .. num_nodes)
+            .filter_map(|i| {
+                let src_node = i;
+                let dst_node = (i * 13 + 5) % num_nodes;
+                let delivery = send_message(src_node, dst_node, &mut towers_reliability,
+                                            &overlay, &honest, &mut rng).unwrap();
+                let path = delivery.path;
+                path[1 .. path.len() - 1].iter()
+                    .cloned()
+                    .find(|&tower_node| tower_node != src_node && tower_node != dst_node &&
+                          overlay.overlay_path_filtered(path[0], path[path.len() - 1],
+                                                        |t| t != tower_node).is_some())
+                    .map(|tower_node| (src_node, dst_node, tower_node))
+            })
+            .next()
+            .unwrap();
+
+        // The only free rider is in the middle of the overlay path:
+        let mut free_riders = FreeRiders::new(&chosen_towers, 0.0, 1.0, &mut rng);
+        free_riders.free_riders.insert(middle_tower);
+
+        let mut static_reliability = TowersReliability::new(&net, &chosen_towers, 3, 0.5, 0.0);
+        let mut adaptive_reliability = TowersReliability::new(&net, &chosen_towers, 3, 0.5, 0.3);
+        let first_delivery = send_message(src_node, dst_node, &mut adaptive_reliability,
+                                          &overlay, &free_riders, &mut rng).unwrap();
+        assert!(!first_delivery.delivered);
+        let (src_tower, dst_tower) = (first_delivery.path[0],
+                                      first_delivery.path[first_delivery.path.len() - 1]);
+        for _ in 0 .. 4 {
+            let delivery = send_message(src_node, dst_node, &mut static_reliability,
+                                        &overlay, &free_riders, &mut rng).unwrap();
+            assert!(!delivery.delivered);
+            send_message(src_node, dst_node, &mut adaptive_reliability,
+                         &overlay, &free_riders, &mut rng).unwrap();
+        }
+
+        // Only the free rider is blamed, and the message is routed around it:
+        assert!(!adaptive_reliability.is_reliable(src_node, middle_tower));
+        assert!(adaptive_reliability.is_reliable(src_node, src_tower));
+        assert!(adaptive_reliability.is_reliable(src_node, dst_tower));
+        assert!(adaptive_reliability.num_switches() == 0);
+        let delivery = send_message(src_node, dst_node, &mut adaptive_reliability,
+                                    &overlay, &free_riders, &mut rng).unwrap();
+        assert!(delivery.delivered);
+        assert!(delivery.tower_color == first_delivery.tower_color);
+        assert!(!delivery.path.contains(&middle_tower));
+        assert!(delivery.path[0] == src_tower);
+        assert!(delivery.path[delivery.path.len() - 1] == dst_tower);
+    }
+}
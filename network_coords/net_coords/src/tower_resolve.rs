@@ -0,0 +1,317 @@
+extern crate ring;
+
+use std::hash::Hash;
+use std::collections::{HashMap, BTreeMap};
+
+use self::ring::{digest};
+
+use towers::{LocalTowerInfo, TowersOverlay};
+
+/// Hash of the id of a node (hashNodeId).
+pub fn hash_node_id(node: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(
+        &digest::digest(&digest::SHA256, &(node as u64).to_be_bytes()).as_ref()[.. 8]);
+    u64::from_be_bytes(bytes)
+}
+
+/// The tower of color tower_color responsible for a hash.
+/// Every color has its own responsible tower, picked by the hash.
+pub fn responsible_tower(hash: u64, chosen_towers: &Vec<Vec<usize>>, 
+                         tower_color: usize) -> usize {
+    let ctowers = &chosen_towers[tower_color];
+    ctowers[(hash % (ctowers.len() as u64)) as usize]
+}
+
+/// A cache that keeps at most capacity entries.
+/// When full, the least recently used entry is evicted.
+pub struct LruCache<K, V> {
+    capacity: usize,
+    /// For every key: Its value, and the time it was last used.
+    entries: HashMap<K, (V, u64)>,
+    /// Keys ordered by the time they were last used.
+    last_used: BTreeMap<u64, K>,
+    time: u64,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> LruCache<K, V> {
+        LruCache {
+            capacity,
+            entries: HashMap::new(),
+            last_used: BTreeMap::new(),
+            time: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get the value of key, and mark key as recently used.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.time += 1;
+        let time = self.time;
+        let entry = self.entries.get_mut(key)?;
+        self.last_used.remove(&entry.1);
+        self.last_used.insert(time, key.clone());
+        entry.1 = time;
+        Some(&entry.0)
+    }
+
+    /// Insert a value for key, evicting the least recently used entry if the cache is full.
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.time += 1;
+        if let Some((_, old_time)) = self.entries.remove(&key) {
+            self.last_used.remove(&old_time);
+        } else if self.entries.len() >= self.capacity {
+            let oldest_time = *self.last_used.keys().next().unwrap();
+            let oldest_key = self.last_used.remove(&oldest_time).unwrap();
+            self.entries.remove(&oldest_key);
+        }
+        self.last_used.insert(self.time, key.clone());
+        self.entries.insert(key, (value, self.time));
+    }
+}
+
+/// Result of resolving a hash of a node id.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupResult {
+    pub node: usize,
+    /// Length of the path of the request and the response.
+    pub path_len: u64,
+    /// Was the response found in the cache of the local tower?
+    pub cache_hit: bool,
+}
+
+/// A resolve service (hashNodeId to nodeId) given by towers.
+/// Every node registers with the towers responsible for its hash, one of every color.
+/// A lookup goes through the responsible tower closest to the requesting node: It is sent to
+/// the local tower of the same color, which answers from its cache, or forwards the lookup
+/// through the overlay to the responsible tower, and caches the response.
+pub struct ResolveService {
+    chosen_towers: Vec<Vec<usize>>,
+    /// Registrations at every tower: hashNodeId -> nodeId.
+    registrations: HashMap<usize, HashMap<u64, usize>>,
+    /// Cache of lookup responses at every tower.
+    caches: HashMap<usize, LruCache<u64, usize>>,
+    cache_capacity: usize,
+}
+
+impl ResolveService {
+    pub fn new(chosen_towers: &Vec<Vec<usize>>, cache_capacity: usize) -> ResolveService {
+        ResolveService {
+            chosen_towers: chosen_towers.clone(),
+            registrations: HashMap::new(),
+            caches: HashMap::new(),
+            cache_capacity,
+        }
+    }
+
+    /// Register node with the towers responsible for its hash, one of every color.
+    /// The registration to the responsible tower of some color goes through the local tower of
+    /// node of the same color. Towers that can not be reached are skipped.
+    /// Returns the total length of the paths to the towers, or None if no tower was reached.
+    pub fn register(&mut self, node: usize, towers_info: &Vec<Vec<Option<LocalTowerInfo>>>,
+                    overlay: &TowersOverlay) -> Option<u64> {
+
+        let hash = hash_node_id(node);
+        let mut total_len: Option<u64> = None;
+        for (tower_color, local_info) in towers_info[node].iter().enumerate() {
+            let tower_node = responsible_tower(hash, &self.chosen_towers, tower_color);
+            let local_info = match *local_info {
+                Some(ref local_info) => local_info,
+                None => continue,
+            };
+            let overlay_len = match overlay.overlay_dist(local_info.tower_node(), tower_node) {
+                Some(overlay_len) => overlay_len,
+                None => continue,
+            };
+            self.registrations.entry(tower_node)
+                .or_default()
+                .insert(hash, node);
+            total_len = Some(total_len.unwrap_or(0) + local_info.distance() + overlay_len);
+        }
+        total_len
+    }
+
+    /// Choose the responsible tower for a lookup of hash by src_node: The one with the shortest
+    /// path from src_node through its local tower of the same color, and back.
+    /// Returns (local tower info, responsible tower, overlay length of the round trip),
+    /// or None if no responsible tower can be reached.
+    fn lookup_tower<'a>(&self, src_node: usize, hash: u64,
+                        towers_info: &'a Vec<Vec<Option<LocalTowerInfo>>>,
+                        overlay: &TowersOverlay) -> Option<(&'a LocalTowerInfo, usize, u64)> {
+
+        (0 .. self.chosen_towers.len())
+            .filter_map(|tower_color| {
+                let local_info = towers_info[src_node][tower_color].as_ref()?;
+                let local_tower = local_info.tower_node();
+                let tower_node = responsible_tower(hash, &self.chosen_towers, tower_color);
+                let overlay_len = if local_tower == tower_node {
+                    0
+                } else {
+                    overlay.overlay_dist(local_tower, tower_node)? +
+                        overlay.overlay_dist(tower_node, local_tower)?
+                };
+                Some((2 * local_info.distance() + overlay_len, tower_color,
+                      (local_info, tower_node, overlay_len)))
+            })
+            .min_by_key(|&(total_len, tower_color, _)| (total_len, tower_color))
+            .map(|(_, _, lookup_tower)| lookup_tower)
+    }
+
+    /// Resolve hash to a node id, for src_node.
+    /// Returns None if the hash is not registered, or no path was found.
+    pub fn lookup(&mut self, src_node: usize, hash: u64,
+                  towers_info: &Vec<Vec<Option<LocalTowerInfo>>>,
+                  overlay: &TowersOverlay) -> Option<LookupResult> {
+
+        let (local_info, tower_node, overlay_len) = 
+            self.lookup_tower(src_node, hash, towers_info, overlay)?;
+        let local_tower = local_info.tower_node();
+        // The request goes up to the local tower, and the response goes back down:
+        let climb_len = 2 * local_info.distance();
+
+        let cache_capacity = self.cache_capacity;
+        let cache = self.caches.entry(local_tower)
+            .or_insert_with(|| LruCache::new(cache_capacity));
+        if let Some(&node) = cache.get(&hash) {
+            return Some(LookupResult {
+                node,
+                path_len: climb_len,
+                cache_hit: true,
+            });
+        }
+
+        let node = *self.registrations.get(&tower_node)?.get(&hash)?;
+        if local_tower != tower_node {
+            cache.insert(hash, node);
+        }
+        Some(LookupResult {
+            node,
+            path_len: climb_len + overlay_len,
+            cache_hit: false,
+        })
+    }
+
+    /// Amount of registrations stored at every tower, in the order of the given towers.
+    pub fn storage_load(&self, towers: &[usize]) -> Vec<usize> {
+        towers.iter()
+            .map(|tower_node| self.registrations.get(tower_node)
+                 .map(|tower_registrations| tower_registrations.len())
+                 .unwrap_or(0))
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    extern crate rand;
+    use self::rand::{StdRng, SeedableRng};
+    use network_gen::{gen_network};
+    use towers::{choose_towers, calc_towers_info};
+
+    #[test]
+    fn test_lru_cache() {
+        let mut cache: LruCache<u64, usize> = LruCache::new(2);
+        assert!(cache.is_empty());
+        cache.insert(1, 10);
+        cache.insert(2, 20);
+        // 1 becomes the most recently used:
+        assert!(cache.get(&1) == Some(&10));
+        cache.insert(3, 30);
+        assert!(cache.len() == 2);
+        assert!(cache.get(&2).is_none());
+        assert!(cache.get(&1) == Some(&10));
+        assert!(cache.get(&3) == Some(&30));
+        // Replacing a value does not evict:
+        cache.insert(3, 31);
+        assert!(cache.get(&1) == Some(&10));
+        assert!(cache.get(&3) == Some(&31));
+
+        let mut no_cache: LruCache<u64, usize> = LruCache::new(0);
+        no_cache.insert(1, 10);
+        assert!(no_cache.get(&1).is_none());
+    }
+
+    #[test]
+    fn test_resolve_service() {
+        let seed: &[_] = &[1,2,3,4,16];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let net = gen_network(0, 7, 15, 1, 2, &mut rng);
+        let num_nodes = net.igraph.node_count();
+
+        let chosen_towers = choose_towers(&net, 3, 8, &mut rng);
+        let towers_info = calc_towers_info(&net, &chosen_towers);
+        let overlay = TowersOverlay::new(&chosen_towers, &towers_info);
+
+        let mut service = ResolveService::new(&chosen_towers, 16);
+        for node in 0 .. num_nodes {
+            assert!(service.register(node, &towers_info, &overlay).is_some());
+        }
+        // Every node is registered with a responsible tower of every color:
+        for node in 0 .. num_nodes {
+            for tower_color in 0 .. chosen_towers.len() {
+                let tower_node = responsible_tower(hash_node_id(node), &chosen_towers,
+                                                   tower_color);
+                assert!(service.registrations[&tower_node][&hash_node_id(node)] == node);
+            }
+        }
+        let mut towers = chosen_towers.iter().flatten().cloned().collect::<Vec<usize>>();
+        towers.sort();
+        towers.dedup();
+        let load = service.storage_load(&towers);
+        // A node may be a tower of more than one color:
+        assert!(load.iter().sum::<usize>() >= num_nodes * chosen_towers[0].len());
+        assert!(load.iter().sum::<usize>() <= num_nodes * chosen_towers.len());
+
+        for i in 0 .. 50 {
+            let src_node = (i * 7) % num_nodes;
+            let dst_node = (i * 13 + 5) % num_nodes;
+            let result = service.lookup(src_node, hash_node_id(dst_node),
+                                        &towers_info, &overlay).unwrap();
+            assert!(result.node == dst_node);
+
+            // A second lookup is answered by the local tower, unless it is the responsible
+            // tower:
+            let result2 = service.lookup(src_node, hash_node_id(dst_node),
+                                         &towers_info, &overlay).unwrap();
+            assert!(result2.node == dst_node);
+            assert!(result2.path_len <= result.path_len);
+            let (local_info, tower_node, _) = service.lookup_tower(src_node,
+                hash_node_id(dst_node), &towers_info, &overlay).unwrap();
+            assert!(result2.cache_hit == (local_info.tower_node() != tower_node));
+
+            // A lookup that misses the cache goes through the closest responsible tower:
+            if result.cache_hit {
+                continue;
+            }
+            for (tower_color, local_tower) in towers_info[src_node].iter().enumerate() {
+                let local_tower = local_tower.as_ref().unwrap();
+                let tower_node = responsible_tower(hash_node_id(dst_node), &chosen_towers,
+                                                   tower_color);
+                let overlay_len = if local_tower.tower_node() == tower_node {
+                    0
+                } else {
+                    overlay.overlay_dist(local_tower.tower_node(), tower_node).unwrap() +
+                        overlay.overlay_dist(tower_node, local_tower.tower_node()).unwrap()
+                };
+                assert!(result.path_len <= 2 * local_tower.distance() + overlay_len);
+            }
+        }
+
+        // Unregistered hashes can not be resolved:
+        let mut empty_service = ResolveService::new(&chosen_towers, 16);
+        assert!(empty_service.lookup(0, hash_node_id(1), &towers_info, &overlay).is_none());
+    }
+}
@@ -1,26 +1,46 @@
 extern crate rand;
 extern crate petgraph;
+extern crate ring;
 
 use self::rand::{Rng};
 use self::petgraph::graphmap;
-use self::petgraph::algo::{kosaraju_scc, connected_components};
+use self::petgraph::visit::{NodeFiltered};
+use self::petgraph::algo::{kosaraju_scc, connected_components, dijkstra, astar};
+use self::ring::signature::{Ed25519KeyPair};
 
 use network::{Network};
 use std::hash::Hash;
 
 use random_util::{choose_k_nums};
-use std::collections::VecDeque;
+use signed_chain::{SignedChain, public_key_bytes};
+use std::collections::{VecDeque, BinaryHeap, HashMap};
+use std::cmp::Reverse;
 
 
 /// Information of some node in the network about 
 /// a local tower (Closest of a certain color).
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct LocalTowerInfo {
     gateway: usize,
     distance: u64,
     tower_node: usize,
 }
 
+impl LocalTowerInfo {
+    /// The next node on the path to the local tower.
+    pub fn gateway(&self) -> usize {
+        self.gateway
+    }
+
+    pub fn distance(&self) -> u64 {
+        self.distance
+    }
+
+    pub fn tower_node(&self) -> usize {
+        self.tower_node
+    }
+}
+
 /// Choose nodes to be towers. We pick num_towers towers of every color. There are num_colors
 /// different tower colors.
 pub fn choose_towers<Node: Hash + Eq + Clone, R: Rng>(net: &Network<Node>, 
@@ -122,14 +142,14 @@ pub fn calc_towers_info_mem_heavy<Node: Hash + Eq + Clone>(net: &Network<Node>,
         // Update local tower information:
         *ltower_info_opt = Some(oper.local_tower_info.clone());
         // Notify all neighbors about new information:
-        for nei in net.igraph.neighbors(oper.node) {
+        for (_, nei, &weight) in net.igraph.edges(oper.node) {
             pending_opers.push_back(UpdateOper {
                 node: nei,
                 tower_color: oper.tower_color,
                 tower_index: oper.tower_index,
                 local_tower_info: LocalTowerInfo {
                     gateway: oper.node,
-                    distance: oper.local_tower_info.distance + 1,
+                    distance: oper.local_tower_info.distance + weight,
                     tower_node: oper.local_tower_info.tower_node,
                 }
             });
@@ -140,81 +160,251 @@ pub fn calc_towers_info_mem_heavy<Node: Hash + Eq + Clone>(net: &Network<Node>,
 }
 
 
-/// Perform one iteration of calculating towers info.
-/// Return whether any changed happen during this iteration.
-fn iter_towers_info<Node: Hash + Eq + Clone>(net: &Network<Node>,
-                 chosen_towers: &Vec<Vec<usize>>,
-                 towers_info: &mut Vec<Vec<Option<LocalTowerInfo>>>) -> bool {
-
-    let mut changed = false;
+/// An offer of a local tower to a node:
+/// (distance, gateway, tower_node, node, tower_color)
+type TowerOffer = (u64, usize, usize, usize, usize);
 
-    for node in net.igraph.nodes() {
-        for nei in net.igraph.neighbors(node) {
-            for tower_color in 0 .. chosen_towers.len() {
-                if towers_info[node][tower_color].is_none() {
-                    continue
-                }
-                // This is the candidate LocalTowerInfo for nei:
-                let mut candidate_info = towers_info[node][tower_color].clone().unwrap();
-                candidate_info.distance += 1;
-                candidate_info.gateway = node;
-                // Current nei's LocalTowerInfo:
-
-                if towers_info[nei][tower_color].is_none() {
-                    changed = true;
-                    towers_info[nei][tower_color] = Some(candidate_info);
-                    continue
-                }
+/// Converge information about local towers. 
+/// Every node will learn about the closest local towers
+/// of every color. Distances are measured by edge weights.
+/// Information is propagated in order of distance, using a priority queue.
+/// Ties are broken by (distance, gateway, tower_node).
+pub fn calc_towers_info<Node: Hash + Eq + Clone>(net: &Network<Node>, 
+    chosen_towers: &Vec<Vec<usize>>) -> Vec<Vec<Option<LocalTowerInfo>>> {
 
-                let nei_info = towers_info[nei][tower_color].clone().unwrap();
+    let mut towers_info = init_towers_info(net.igraph.node_count(), 
+                                           chosen_towers.len());
 
-                if (candidate_info.distance,
-                    candidate_info.gateway,
-                    candidate_info.tower_node) <
-                   (nei_info.distance,
-                    nei_info.gateway,
-                    nei_info.tower_node) {
+    // Pending offers. The smallest offer is popped first.
+    let mut pending: BinaryHeap<Reverse<TowerOffer>> = BinaryHeap::new();
 
-                    changed = true;
-                    towers_info[nei][tower_color] = Some(candidate_info);
+    // Add initial offers from all chosen towers.
+    // Later the information about those towers will propagage all over the network.
+    for tower_color in 0 .. chosen_towers.len() {
+        for &tower_node in &chosen_towers[tower_color] {
+            pending.push(Reverse((0, tower_node, tower_node, tower_node, tower_color)));
+        }
+    }
 
-                }
+    while let Some(Reverse((distance, gateway, tower_node, node, tower_color))) = pending.pop() {
+        if towers_info[node][tower_color].is_some() {
+            // A better offer was already accepted:
+            continue
+        }
+        towers_info[node][tower_color] = Some(LocalTowerInfo {
+            gateway,
+            distance,
+            tower_node,
+        });
+        // Offer the new information to all neighbors:
+        for (_, nei, &weight) in net.igraph.edges(node) {
+            if towers_info[nei][tower_color].is_none() {
+                pending.push(Reverse((distance + weight, node, tower_node, nei, tower_color)));
             }
         }
     }
-    changed
+
+    towers_info
 }
 
+/// Marks a missing entry in CompactTowersInfo.
+const NO_TOWER: u32 = u32::MAX;
 
-/// Converge information about local towers. 
-/// Every node will learn about the closest local towers
-/// of every color.
-pub fn calc_towers_info<Node: Hash + Eq + Clone>(net: &Network<Node>, 
-    chosen_towers: &Vec<Vec<usize>>) -> Vec<Vec<Option<LocalTowerInfo>>> {
+/// Information of all nodes about local towers, stored as a struct of arrays.
+/// Indices and distances are stored as u32, so that networks of size 2^20 with many colors
+/// fit in memory. Entries are ordered by color, and then by node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactTowersInfo {
+    num_nodes: usize,
+    num_colors: usize,
+    gateways: Vec<u32>,
+    distances: Vec<u32>,
+    tower_nodes: Vec<u32>,
+}
 
-    let mut towers_info = init_towers_info(net.igraph.node_count(), 
-                                           chosen_towers.len());
+impl CompactTowersInfo {
+    pub fn new(num_nodes: usize, num_colors: usize) -> CompactTowersInfo {
+        assert!(num_nodes < NO_TOWER as usize, "Too many nodes!");
+        let num_entries = num_nodes * num_colors;
+        CompactTowersInfo {
+            num_nodes,
+            num_colors,
+            gateways: vec![NO_TOWER; num_entries],
+            distances: vec![0; num_entries],
+            tower_nodes: vec![NO_TOWER; num_entries],
+        }
+    }
 
-    // Add initial update operations from all chosen towers.
-    // Later the information about those towers will propagage all over the network.
-    for tower_color in 0 .. chosen_towers.len() {
-        for tower_index in 0 .. chosen_towers[tower_color].len() {
-            let tower_node = chosen_towers[tower_color][tower_index];
-            let tower_info = &mut towers_info[tower_node][tower_color];
-            *tower_info = Some(LocalTowerInfo {
-                gateway: tower_node,
-                distance: 0,
-                tower_node,
-            });
+    pub fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+
+    pub fn num_colors(&self) -> usize {
+        self.num_colors
+    }
+
+    fn entry_index(&self, node: usize, tower_color: usize) -> usize {
+        tower_color * self.num_nodes + node
+    }
+
+    /// Get the local tower of tower_color of node.
+    pub fn get(&self, node: usize, tower_color: usize) -> Option<LocalTowerInfo> {
+        let i = self.entry_index(node, tower_color);
+        if self.gateways[i] == NO_TOWER {
+            return None;
+        }
+        Some(LocalTowerInfo {
+            gateway: self.gateways[i] as usize,
+            distance: self.distances[i] as u64,
+            tower_node: self.tower_nodes[i] as usize,
+        })
+    }
+
+    fn set(&mut self, node: usize, tower_color: usize, distance: u64, 
+           gateway: usize, tower_node: usize) {
+        assert!(distance <= u32::MAX as u64, "Distance is too large!");
+        let i = self.entry_index(node, tower_color);
+        self.gateways[i] = gateway as u32;
+        self.distances[i] = distance as u32;
+        self.tower_nodes[i] = tower_node as u32;
+    }
+
+    /// (distance, gateway, tower_node) of an entry, for comparing entries.
+    /// None if the entry is missing.
+    fn entry_key(&self, node: usize, tower_color: usize) -> Option<(u64, u32, u32)> {
+        let i = self.entry_index(node, tower_color);
+        if self.gateways[i] == NO_TOWER {
+            return None;
         }
+        Some((self.distances[i] as u64, self.gateways[i], self.tower_nodes[i]))
     }
 
-    while iter_towers_info(net, chosen_towers, &mut towers_info) {
+    /// Make sure that all nodes know a local tower of every color.
+    pub fn is_filled(&self) -> bool {
+        self.gateways.iter().all(|&gateway| gateway != NO_TOWER)
+    }
+
+    /// Amount of memory used by the entries, in bytes.
+    pub fn mem_size(&self) -> usize {
+        3 * self.gateways.len() * ::std::mem::size_of::<u32>()
+    }
+
+    /// Convert to the nested vectors representation.
+    pub fn to_towers_info(&self) -> Vec<Vec<Option<LocalTowerInfo>>> {
+        (0 .. self.num_nodes)
+            .map(|node| (0 .. self.num_colors)
+                 .map(|tower_color| self.get(node, tower_color))
+                 .collect())
+            .collect()
+    }
+}
+
+/// Converge information about local towers into a CompactTowersInfo.
+/// Gives the same results as calc_towers_info, using much less memory.
+/// Colors are handled one after the other. For every color, information is propagated
+/// in order of distance, and offers are queued only if they improve the current entry.
+pub fn calc_towers_info_compact<Node: Hash + Eq + Clone>(net: &Network<Node>, 
+    chosen_towers: &Vec<Vec<usize>>) -> CompactTowersInfo {
+
+    let num_nodes = net.igraph.node_count();
+    let mut towers_info = CompactTowersInfo::new(num_nodes, chosen_towers.len());
+
+    // Neighbors of node are adj[adj_start[node] .. adj_start[node + 1]], as (nei, weight).
+    // Looking up edges in the network is slow, so we do it only once for all colors.
+    let mut adj_start: Vec<usize> = Vec::with_capacity(num_nodes + 1);
+    let mut adj: Vec<(u32, u32)> = Vec::with_capacity(2 * net.igraph.edge_count());
+    for node in 0 .. num_nodes {
+        adj_start.push(adj.len());
+        for (_, nei, &weight) in net.igraph.edges(node) {
+            assert!(weight <= u32::MAX as u64, "Weight is too large!");
+            adj.push((nei as u32, weight as u32));
+        }
+    }
+    adj_start.push(adj.len());
+
+    for (tower_color, ctowers) in chosen_towers.iter().enumerate() {
+        // Pending offers: (distance, gateway, tower_node, node).
+        // The smallest offer is popped first.
+        let mut pending: BinaryHeap<Reverse<(u64, u32, u32, u32)>> = BinaryHeap::new();
+        for &tower_node in ctowers {
+            towers_info.set(tower_node, tower_color, 0, tower_node, tower_node);
+            pending.push(Reverse((0, tower_node as u32, tower_node as u32, tower_node as u32)));
+        }
+
+        while let Some(Reverse((distance, gateway, tower_node, node))) = pending.pop() {
+            let node = node as usize;
+            if towers_info.entry_key(node, tower_color) != Some((distance, gateway, tower_node)) {
+                // This offer was replaced by a better one:
+                continue
+            }
+            for &(nei, weight) in &adj[adj_start[node] .. adj_start[node + 1]] {
+                let nei = nei as usize;
+                let offer = (distance + weight as u64, node as u32, tower_node);
+                let is_better = match towers_info.entry_key(nei, tower_color) {
+                    None => true,
+                    Some(nei_key) => offer < nei_key,
+                };
+                if is_better {
+                    towers_info.set(nei, tower_color, offer.0, node, tower_node as usize);
+                    pending.push(Reverse((offer.0, offer.1, offer.2, nei as u32)));
+                }
+            }
+        }
     }
 
     towers_info
 }
 
+/// Choose num_indexers nodes to be indexers. Indexers have no colors.
+pub fn choose_indexers<Node: Hash + Eq + Clone, R: Rng>(net: &Network<Node>, 
+                  num_indexers: usize, rng: &mut R) -> Vec<usize> {
+
+    let mut indexers = choose_k_nums(num_indexers, net.igraph.node_count(), rng)
+        .into_iter()
+        .collect::<Vec<usize>>();
+    // Sort for determinism:
+    indexers.sort();
+    indexers
+}
+
+/// Converge information about local indexers.
+/// Instead of remembering the closest tower of every color, every node will learn about
+/// its k closest indexers. Information about every node is sorted by 
+/// (distance, gateway, tower_node).
+pub fn calc_indexers_info<Node: Hash + Eq + Clone>(net: &Network<Node>, 
+    indexers: &[usize], k: usize) -> Vec<Vec<LocalTowerInfo>> {
+
+    let mut indexers_info: Vec<Vec<LocalTowerInfo>> = 
+        (0 .. net.igraph.node_count()).map(|_| Vec::new()).collect();
+
+    // Pending offers: (distance, gateway, indexer, node).
+    // The smallest offer is popped first.
+    let mut pending: BinaryHeap<Reverse<(u64, usize, usize, usize)>> = BinaryHeap::new();
+    for &indexer in indexers {
+        pending.push(Reverse((0, indexer, indexer, indexer)));
+    }
+
+    while let Some(Reverse((distance, gateway, indexer, node))) = pending.pop() {
+        let node_info = &mut indexers_info[node];
+        if node_info.len() >= k || node_info.iter().any(|info| info.tower_node == indexer) {
+            continue
+        }
+        node_info.push(LocalTowerInfo {
+            gateway,
+            distance,
+            tower_node: indexer,
+        });
+        // Offer the new information to all neighbors:
+        for (_, nei, &weight) in net.igraph.edges(node) {
+            if indexers_info[nei].len() < k {
+                pending.push(Reverse((distance + weight, node, indexer, nei)));
+            }
+        }
+    }
+
+    indexers_info
+}
+
 /// Make sure that all LocalTowerInfo fields are not None
 pub fn is_towers_info_filled(towers_info: &Vec<Vec<Option<LocalTowerInfo>>>) -> bool {
     for node in 0 .. towers_info.len() {
@@ -227,41 +417,744 @@ pub fn is_towers_info_filled(towers_info: &Vec<Vec<Option<LocalTowerInfo>>>) ->
     return true;
 }
 
+/// Statistics of the re-propagation of towers info after a tower was added or removed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TowersChangeStats {
+    /// Amount of messages sent between neighbors.
+    pub num_messages: usize,
+    /// Amount of rounds until towers info converged.
+    pub num_rounds: usize,
+    /// Amount of rounds at the start of which some node had no local tower of some color.
+    pub num_unfilled_rounds: usize,
+    /// Amount of nodes that have received the removal notice of a removed tower.
+    pub num_notified: usize,
+}
+
+/// Calculate the best local tower of tower_color for node, 
+/// given the current towers info of its neighbors.
+/// Information about removed_tower (A removed tower that node has learned about) is ignored.
+/// Information of neighbors that use node as a gateway is also ignored (Split horizon).
+fn best_tower_info<Node: Hash + Eq + Clone>(net: &Network<Node>,
+                   chosen_towers: &Vec<Vec<usize>>,
+                   towers_info: &Vec<Vec<Option<LocalTowerInfo>>>,
+                   node: usize, tower_color: usize,
+                   removed_tower: Option<usize>) -> Option<LocalTowerInfo> {
+
+    if chosen_towers[tower_color].binary_search(&node).is_ok() {
+        return Some(LocalTowerInfo {
+            gateway: node,
+            distance: 0,
+            tower_node: node,
+        });
+    }
+
+    net.igraph.edges(node)
+        .filter_map(|(_, nei, &weight)| {
+            let nei_info = towers_info[nei][tower_color].as_ref()?;
+            if nei_info.gateway == node || Some(nei_info.tower_node) == removed_tower {
+                return None;
+            }
+            Some(LocalTowerInfo {
+                gateway: nei,
+                distance: nei_info.distance + weight,
+                tower_node: nei_info.tower_node,
+            })
+        })
+        .min_by_key(|info| (info.distance, info.gateway, info.tower_node))
+}
+
+/// Re-propagate towers info of tower_color, in synchronous rounds.
+/// changed_nodes are the nodes that have just changed their local tower of tower_color.
+/// In every round, every node that has changed sends its new information to all of its
+/// neighbors, and every neighbor recalculates its local tower. A node that has lost its
+/// local tower also gets the information of all of its neighbors, and recalculates.
+/// If removed_tower is given, it is the only node that knows about its removal at first.
+/// The removal notice is carried by every message of a node that knows about it, and only
+/// nodes that have received it ignore information about removed_tower.
+fn repropagate_towers_info<Node: Hash + Eq + Clone>(net: &Network<Node>,
+                   chosen_towers: &Vec<Vec<usize>>,
+                   towers_info: &mut Vec<Vec<Option<LocalTowerInfo>>>,
+                   tower_color: usize, mut changed_nodes: Vec<usize>,
+                   removed_tower: Option<usize>) -> TowersChangeStats {
+
+    let mut stats = TowersChangeStats::default();
+    // Nodes that know about the removal of removed_tower:
+    let mut notified = vec![false; towers_info.len()];
+    if let Some(tower_node) = removed_tower {
+        notified[tower_node] = true;
+    }
+
+    while !changed_nodes.is_empty() {
+        if !is_towers_info_filled(towers_info) {
+            stats.num_unfilled_rounds += 1;
+        }
+        stats.num_rounds += 1;
+
+        let mut receivers: Vec<usize> = Vec::new();
+        for &node in &changed_nodes {
+            let is_lost = towers_info[node][tower_color].is_none();
+            for nei in net.igraph.neighbors(node) {
+                stats.num_messages += 1;
+                receivers.push(nei);
+                if notified[node] {
+                    notified[nei] = true;
+                }
+                if is_lost {
+                    // node has lost its local tower. All neighbors reply with their information:
+                    stats.num_messages += 1;
+                }
+            }
+            if is_lost {
+                receivers.push(node);
+            }
+        }
+        // Sort for determinism:
+        receivers.sort();
+        receivers.dedup();
+
+        // All receivers recalculate using the information from the start of the round:
+        let updates = receivers.into_iter()
+            .filter_map(|node| {
+                let node_removed_tower = if notified[node] { removed_tower } else { None };
+                let new_info = best_tower_info(net, chosen_towers, towers_info, 
+                                               node, tower_color, node_removed_tower);
+                if new_info != towers_info[node][tower_color] {
+                    Some((node, new_info))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        changed_nodes = updates.iter().map(|&(node, _)| node).collect();
+        for (node, new_info) in updates {
+            towers_info[node][tower_color] = new_info;
+        }
+    }
+
+    stats.num_notified = notified.into_iter().filter(|&is_notified| is_notified).count();
+    stats
+}
+
+/// Make tower_node a new tower of color tower_color, 
+/// and incrementally update towers info of all nodes.
+pub fn add_tower<Node: Hash + Eq + Clone>(net: &Network<Node>,
+                   chosen_towers: &mut Vec<Vec<usize>>,
+                   towers_info: &mut Vec<Vec<Option<LocalTowerInfo>>>,
+                   tower_color: usize, tower_node: usize) -> TowersChangeStats {
+
+    match chosen_towers[tower_color].binary_search(&tower_node) {
+        Ok(_) => panic!("Node is already a tower of this color!"),
+        Err(pos) => chosen_towers[tower_color].insert(pos, tower_node),
+    };
+    towers_info[tower_node][tower_color] = Some(LocalTowerInfo {
+        gateway: tower_node,
+        distance: 0,
+        tower_node,
+    });
+    repropagate_towers_info(net, chosen_towers, towers_info, tower_color, vec![tower_node],
+                            None)
+}
+
+/// Remove tower_node from being a tower of color tower_color (The tower has failed),
+/// and incrementally update towers info of all nodes.
+/// tower_node stays without a local tower of tower_color until it hears from its neighbors.
+/// tower_node sends a removal notice to its neighbors, and the notice spreads along with the
+/// re-propagated information. Information about tower_node is ignored only by nodes that have
+/// received the notice.
+pub fn remove_tower<Node: Hash + Eq + Clone>(net: &Network<Node>,
+                   chosen_towers: &mut Vec<Vec<usize>>,
+                   towers_info: &mut Vec<Vec<Option<LocalTowerInfo>>>,
+                   tower_color: usize, tower_node: usize) -> TowersChangeStats {
+
+    match chosen_towers[tower_color].binary_search(&tower_node) {
+        Ok(pos) => chosen_towers[tower_color].remove(pos),
+        Err(_) => panic!("Node is not a tower of this color!"),
+    };
+    towers_info[tower_node][tower_color] = None;
+    repropagate_towers_info(net, chosen_towers, towers_info, tower_color, vec![tower_node],
+                            Some(tower_node))
+}
+
+/// Bandwidth used by a group of participants (Nodes or towers).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BandwidthStats {
+    /// Amount of synchronous rounds until convergence.
+    pub num_rounds: usize,
+    /// Amount of messages sent by every participant.
+    pub sent_messages: Vec<usize>,
+    /// Amount of entries (A tower and a distance) sent by every participant.
+    pub sent_entries: Vec<usize>,
+}
+
+impl BandwidthStats {
+    pub fn avg_messages(&self) -> f64 {
+        (self.sent_messages.iter().sum::<usize>() as f64) / (self.sent_messages.len() as f64)
+    }
+
+    pub fn max_messages(&self) -> usize {
+        self.sent_messages.iter().cloned().max().unwrap_or(0)
+    }
+
+    pub fn avg_entries(&self) -> f64 {
+        (self.sent_entries.iter().sum::<usize>() as f64) / (self.sent_entries.len() as f64)
+    }
+
+    pub fn max_entries(&self) -> usize {
+        self.sent_entries.iter().cloned().max().unwrap_or(0)
+    }
+}
+
+/// Converge towers info from scratch, by sending messages between neighbors in synchronous
+/// rounds. All colors converge together: In every round, every node that has changed its
+/// local towers sends one message to each of its neighbors, carrying an entry for every
+/// color that has changed.
+/// Returns the resulting towers info, and the bandwidth used by every node.
+pub fn calc_towers_info_bandwidth<Node: Hash + Eq + Clone>(net: &Network<Node>, 
+    chosen_towers: &Vec<Vec<usize>>) -> (Vec<Vec<Option<LocalTowerInfo>>>, BandwidthStats) {
+
+    let num_nodes = net.igraph.node_count();
+    let mut towers_info = init_towers_info(num_nodes, chosen_towers.len());
+    // Colors changed by every node in the last round:
+    let mut changed: Vec<Vec<usize>> = vec![Vec::new(); num_nodes];
+    for (tower_color, ctowers) in chosen_towers.iter().enumerate() {
+        for &tower_node in ctowers {
+            towers_info[tower_node][tower_color] = Some(LocalTowerInfo {
+                gateway: tower_node,
+                distance: 0,
+                tower_node,
+            });
+            changed[tower_node].push(tower_color);
+        }
+    }
+
+    let mut bandwidth = BandwidthStats {
+        num_rounds: 0,
+        sent_messages: vec![0; num_nodes],
+        sent_entries: vec![0; num_nodes],
+    };
+
+    while changed.iter().any(|node_changed| !node_changed.is_empty()) {
+        bandwidth.num_rounds += 1;
+
+        // (node, tower_color) pairs that have received new information:
+        let mut receivers: Vec<(usize, usize)> = Vec::new();
+        for (node, node_changed) in changed.iter().enumerate() {
+            if node_changed.is_empty() {
+                continue;
+            }
+            for nei in net.igraph.neighbors(node) {
+                bandwidth.sent_messages[node] += 1;
+                bandwidth.sent_entries[node] += node_changed.len();
+                receivers.extend(node_changed.iter().map(|&tower_color| (nei, tower_color)));
+            }
+        }
+        // Sort for determinism:
+        receivers.sort();
+        receivers.dedup();
+
+        // All receivers recalculate using the information from the start of the round:
+        let updates = receivers.into_iter()
+            .filter_map(|(node, tower_color)| {
+                let new_info = best_tower_info(net, chosen_towers, &towers_info,
+                                               node, tower_color, None);
+                if new_info != towers_info[node][tower_color] {
+                    Some((node, tower_color, new_info))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        changed = vec![Vec::new(); num_nodes];
+        for (node, tower_color, new_info) in updates {
+            changed[node].push(tower_color);
+            towers_info[node][tower_color] = new_info;
+        }
+    }
+
+    (towers_info, bandwidth)
+}
+
+/// Converge the distance vectors of all towers: Every tower learns its distance to every
+/// other tower, going through the towers overlay. Whenever distances to some towers become
+/// shorter, a tower sends the changed entries to all towers that are directly connected to it
+/// in the overlay.
+/// Returns the list of all towers, their distance vectors (In the order of the list), and the
+/// bandwidth used by every tower (In the order of the list).
+pub fn calc_towers_dist_vectors(chosen_towers: &Vec<Vec<usize>>, 
+        towers_info: &Vec<Vec<Option<LocalTowerInfo>>>) 
+    -> (Vec<usize>, Vec<Vec<Option<u64>>>, BandwidthStats) {
+
+    let overlay = TowersOverlay::new(chosen_towers, towers_info);
+    let mut towers = overlay.graph.nodes().collect::<Vec<usize>>();
+    // Sort for determinism:
+    towers.sort();
+    let num_towers = towers.len();
+    let tower_index = |tower_node: usize| towers.binary_search(&tower_node).unwrap();
+
+    // Towers that are directly connected to every tower:
+    let mut in_neighbors: Vec<Vec<(usize, u64)>> = vec![Vec::new(); num_towers];
+    for (a, b, &weight) in overlay.graph.all_edges() {
+        if a != b {
+            in_neighbors[tower_index(b)].push((tower_index(a), weight));
+        }
+    }
+
+    let mut dist_vectors: Vec<Vec<Option<u64>>> = vec![vec![None; num_towers]; num_towers];
+    let mut changed: Vec<Vec<usize>> = Vec::new();
+    for (i, dist_vector) in dist_vectors.iter_mut().enumerate() {
+        dist_vector[i] = Some(0);
+        changed.push(vec![i]);
+    }
+
+    let mut bandwidth = BandwidthStats {
+        num_rounds: 0,
+        sent_messages: vec![0; num_towers],
+        sent_entries: vec![0; num_towers],
+    };
+
+    while changed.iter().any(|tower_changed| !tower_changed.is_empty()) {
+        bandwidth.num_rounds += 1;
+        // Receivers use the distance vectors from the start of the round:
+        let old_dist_vectors = dist_vectors.clone();
+        let mut new_changed: Vec<Vec<usize>> = vec![Vec::new(); num_towers];
+        for sender in 0 .. num_towers {
+            if changed[sender].is_empty() {
+                continue;
+            }
+            for &(receiver, weight) in &in_neighbors[sender] {
+                bandwidth.sent_messages[sender] += 1;
+                bandwidth.sent_entries[sender] += changed[sender].len();
+                for &target in &changed[sender] {
+                    let new_dist = old_dist_vectors[sender][target].unwrap() + weight;
+                    let is_better = match dist_vectors[receiver][target] {
+                        None => true,
+                        Some(cur_dist) => new_dist < cur_dist,
+                    };
+                    if is_better {
+                        dist_vectors[receiver][target] = Some(new_dist);
+                        new_changed[receiver].push(target);
+                    }
+                }
+            }
+        }
+        for tower_changed in &mut new_changed {
+            tower_changed.sort();
+            tower_changed.dedup();
+        }
+        changed = new_changed;
+    }
+
+    (towers, dist_vectors, bandwidth)
+}
+
+/// An update about a local tower, sent from a node to its neighbor.
+/// The chain proves an upper bound on the amount of hops from the tower to the receiver.
+#[derive(Clone, Debug)]
+pub struct TowerUpdate {
+    pub tower_color: usize,
+    /// Local tower information offered to the receiver (The sender is the gateway).
+    pub local_tower_info: LocalTowerInfo,
+    pub chain: SignedChain,
+}
+
+/// Build the signed chain held by node for its local tower of tower_color.
+/// The chain follows the gateways from the tower to node, and every node on the way signs
+/// the next hop. key_pairs contains the key pair of every node.
+/// Returns None if node has no local tower of tower_color.
+pub fn tower_chain(towers_info: &Vec<Vec<Option<LocalTowerInfo>>>, node: usize,
+                   tower_color: usize, key_pairs: &[Ed25519KeyPair], 
+                   timestamp: u64) -> Option<SignedChain> {
+
+    // Path from node to the tower:
+    let mut path = vec![node];
+    loop {
+        let cur_node = *path.last().unwrap();
+        let ltower_info = towers_info[cur_node][tower_color].as_ref()?;
+        if ltower_info.gateway == cur_node {
+            break;
+        }
+        path.push(ltower_info.gateway);
+    }
+
+    let mut chain = SignedChain::new(&public_key_bytes(&key_pairs[*path.last().unwrap()]),
+                                     timestamp);
+    for i in (1 .. path.len()).rev() {
+        chain = chain.extend(&key_pairs[path[i]], &public_key_bytes(&key_pairs[path[i - 1]]));
+    }
+    Some(chain)
+}
+
+/// Create a signed update from sender to its neighbor receiver, 
+/// about the local tower of tower_color of sender.
+/// Returns None if sender has no local tower of tower_color.
+pub fn make_tower_update<Node: Hash + Eq + Clone>(net: &Network<Node>,
+                   towers_info: &Vec<Vec<Option<LocalTowerInfo>>>,
+                   sender: usize, receiver: usize, tower_color: usize, 
+                   key_pairs: &[Ed25519KeyPair], timestamp: u64) -> Option<TowerUpdate> {
+
+    let weight = *net.igraph.edge_weight(sender, receiver)
+        .expect("Sender and receiver are not neighbors!");
+    let sender_info = towers_info[sender][tower_color].as_ref()?;
+    let chain = tower_chain(towers_info, sender, tower_color, key_pairs, timestamp)?
+        .extend(&key_pairs[sender], &public_key_bytes(&key_pairs[receiver]));
+
+    Some(TowerUpdate {
+        tower_color,
+        local_tower_info: LocalTowerInfo {
+            gateway: sender,
+            distance: sender_info.distance + weight,
+            tower_node: sender_info.tower_node,
+        },
+        chain,
+    })
+}
+
+/// Verify a tower update received by receiver: The chain must be fresh, all of its signatures
+/// must be valid, it must start at the offered tower, and its last hop must be signed by the
+/// gateway and lead to the receiver. public_keys contains the public key of every node.
+/// Every edge weighs at least 1, so the offered distance may not be shorter than the amount
+/// of hops in the chain.
+pub fn verify_tower_update(update: &TowerUpdate, receiver: usize, public_keys: &[Vec<u8>],
+                           now: u64, max_age: u64) -> bool {
+
+    let nodes = update.chain.nodes();
+    if nodes.len() < 2 {
+        return false;
+    }
+    let ltower_info = &update.local_tower_info;
+    ltower_info.distance >= update.chain.len() as u64 &&
+        nodes[0] == &public_keys[ltower_info.tower_node][..] &&
+        nodes[nodes.len() - 2] == &public_keys[ltower_info.gateway][..] &&
+        nodes[nodes.len() - 1] == &public_keys[receiver][..] &&
+        update.chain.verify(now, max_age)
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 struct TowerGraphNode {
     tower_color: usize,
     tower_index: usize,
 }
 
+/// An overlay directed graph of the towers in the network and the connections between them.
+/// A tower T is connected to a tower T' if T' is the closest tower to T of some color.
+/// The weight of a connection is its length (Sum of edge weights).
+pub struct TowersOverlay {
+    graph: graphmap::DiGraphMap<usize, u64>,
+}
+
+impl TowersOverlay {
+    pub fn new(chosen_towers: &Vec<Vec<usize>>, 
+               towers_info: &Vec<Vec<Option<LocalTowerInfo>>>) -> TowersOverlay {
+
+        let mut graph: graphmap::DiGraphMap<usize, u64> = graphmap::DiGraphMap::new();
+
+        // Add towers as nodes to the graph:
+        for tower_color in 0 .. chosen_towers.len() {
+            for tower_index in 0 .. chosen_towers[tower_color].len() {
+                graph.add_node(chosen_towers[tower_color][tower_index]);
+            }
+        }
+
+        // For every tower, add all connections to closest local towers as nodes.
+        // Colors without towers are skipped.
+        let graph_nodes = graph.nodes().collect::<Vec<usize>>();
+        for tower_node in graph_nodes {
+            for tower_color in 0 .. chosen_towers.len() {
+                if let Some(ref ltower_info) = towers_info[tower_node][tower_color] {
+                    graph.add_edge(tower_node, ltower_info.tower_node, ltower_info.distance);
+                }
+            }
+        }
+
+        TowersOverlay {
+            graph,
+        }
+    }
+
+    /// Build the overlay from a CompactTowersInfo.
+    pub fn new_compact(chosen_towers: &Vec<Vec<usize>>, 
+                       towers_info: &CompactTowersInfo) -> TowersOverlay {
+
+        let mut graph: graphmap::DiGraphMap<usize, u64> = graphmap::DiGraphMap::new();
+        for &tower_node in chosen_towers.iter().flatten() {
+            graph.add_node(tower_node);
+        }
+        let graph_nodes = graph.nodes().collect::<Vec<usize>>();
+        for tower_node in graph_nodes {
+            for tower_color in 0 .. chosen_towers.len() {
+                if let Some(ltower_info) = towers_info.get(tower_node, tower_color) {
+                    graph.add_edge(tower_node, ltower_info.tower_node, ltower_info.distance);
+                }
+            }
+        }
+
+        TowersOverlay {
+            graph,
+        }
+    }
+
+    /// Build an overlay of indexers. An indexer I is connected to an indexer I' if I' is one
+    /// of the k closest indexers to I.
+    pub fn new_indexers(indexers: &[usize], 
+                        indexers_info: &Vec<Vec<LocalTowerInfo>>) -> TowersOverlay {
+
+        let mut graph: graphmap::DiGraphMap<usize, u64> = graphmap::DiGraphMap::new();
+        for &indexer in indexers {
+            graph.add_node(indexer);
+        }
+        for &indexer in indexers {
+            for lindexer_info in &indexers_info[indexer] {
+                graph.add_edge(indexer, lindexer_info.tower_node, lindexer_info.distance);
+            }
+        }
+
+        TowersOverlay {
+            graph,
+        }
+    }
+
+    /// Check if the overlay is connected.
+    /// Returns (connected, strongly_connected)
+    pub fn is_connected(&self) -> (bool, bool) {
+        let sconnected_comps = kosaraju_scc(&self.graph);
+        (connected_components(&self.graph) == 1, sconnected_comps.len() == 1)
+    }
+
+    /// Add a directed link between two towers, of the given length.
+    pub fn add_link(&mut self, src_tower: usize, dst_tower: usize, length: u64) {
+        self.graph.add_edge(src_tower, dst_tower, length);
+    }
+
+    /// Find a minimum set of extra links between towers that makes the overlay strongly
+    /// connected (Eswaran and Tarjan). If the strongly connected components of the overlay 
+    /// have num_sources sources and num_sinks sinks, max(num_sources, num_sinks) links are 
+    /// required (Or no links if the overlay is already strongly connected).
+    /// Every link is from a tower in a sink component to a tower in a source component.
+    pub fn strong_connectivity_links(&self) -> Vec<(usize, usize)> {
+        let mut sccs = kosaraju_scc(&self.graph);
+        if sccs.len() <= 1 {
+            return Vec::new();
+        }
+        // Sort for determinism:
+        for scc in &mut sccs {
+            scc.sort();
+        }
+        sccs.sort();
+
+        let mut scc_of: HashMap<usize, usize> = HashMap::new();
+        for (i, scc) in sccs.iter().enumerate() {
+            for &tower_node in scc {
+                scc_of.insert(tower_node, i);
+            }
+        }
+
+        // The condensation of the overlay: A DAG of strongly connected components.
+        let num_sccs = sccs.len();
+        let mut out_sccs: Vec<Vec<usize>> = vec![Vec::new(); num_sccs];
+        let mut in_sccs: Vec<Vec<usize>> = vec![Vec::new(); num_sccs];
+        for (a, b, _) in self.graph.all_edges() {
+            let (scc_a, scc_b) = (scc_of[&a], scc_of[&b]);
+            if scc_a != scc_b {
+                out_sccs[scc_a].push(scc_b);
+                in_sccs[scc_b].push(scc_a);
+            }
+        }
+
+        let sources = (0 .. num_sccs).filter(|&i| in_sccs[i].is_empty()).collect::<Vec<_>>();
+        let sinks = (0 .. num_sccs).filter(|&i| out_sccs[i].is_empty()).collect::<Vec<_>>();
+
+        // The construction assumes that there are no more sources than sinks.
+        // Otherwise, we work on the reversed condensation, and reverse the resulting links.
+        let is_reversed = sources.len() > sinks.len();
+        let (sources, sinks, forward) = if is_reversed {
+            (sinks, sources, &in_sccs)
+        } else {
+            (sources, sinks, &out_sccs)
+        };
+
+        // Find a maximal matching of sources to sinks reachable from them:
+        let mut is_sink_matched = vec![false; num_sccs];
+        let mut matched: Vec<(usize, usize)> = Vec::new();
+        let mut unmatched_sources: Vec<usize> = Vec::new();
+        for &source in &sources {
+            let mut visited = vec![false; num_sccs];
+            let mut pending = vec![source];
+            visited[source] = true;
+            let mut found_sink = None;
+            while let Some(scc) = pending.pop() {
+                if forward[scc].is_empty() && !is_sink_matched[scc] {
+                    found_sink = Some(scc);
+                    break;
+                }
+                for &next_scc in &forward[scc] {
+                    if !visited[next_scc] {
+                        visited[next_scc] = true;
+                        pending.push(next_scc);
+                    }
+                }
+            }
+            match found_sink {
+                Some(sink) => {
+                    is_sink_matched[sink] = true;
+                    matched.push((source, sink));
+                },
+                None => unmatched_sources.push(source),
+            }
+        }
+        let unmatched_sinks = sinks.iter()
+            .cloned()
+            .filter(|&sink| !is_sink_matched[sink])
+            .collect::<Vec<usize>>();
+
+        // Links between components, from a sink to a source:
+        let mut scc_links: Vec<(usize, usize)> = Vec::new();
+        // Connect all matched pairs in a cycle:
+        for i in 0 .. matched.len() {
+            let (_, sink) = matched[i];
+            let (next_source, _) = matched[(i + 1) % matched.len()];
+            scc_links.push((sink, next_source));
+        }
+        // Connect every unmatched source to an unmatched sink. 
+        // The unmatched source can reach the cycle, and the cycle can reach the unmatched sink:
+        for (&source, &sink) in unmatched_sources.iter().zip(&unmatched_sinks) {
+            scc_links.push((sink, source));
+        }
+        // Connect the rest of the unmatched sinks back to the cycle:
+        for &sink in &unmatched_sinks[unmatched_sources.len() ..] {
+            scc_links.push((sink, matched[0].0));
+        }
+
+        // Every component is represented by its smallest tower:
+        scc_links.into_iter()
+            .map(|(sink, source)| if is_reversed {
+                (sccs[source][0], sccs[sink][0])
+            } else {
+                (sccs[sink][0], sccs[source][0])
+            })
+            .collect()
+    }
+
+    /// Length of the shortest path in the overlay between two towers.
+    /// Returns None if there is no such path.
+    pub fn overlay_dist(&self, src_tower: usize, dst_tower: usize) -> Option<u64> {
+        let scores = dijkstra(&self.graph, src_tower, Some(dst_tower), |(_, _, &weight)| weight);
+        scores.get(&dst_tower).cloned()
+    }
+
+    /// A shortest path in the overlay between two towers, including both towers.
+    /// Returns (length, towers), or None if there is no such path.
+    pub fn overlay_path(&self, src_tower: usize, dst_tower: usize) -> Option<(u64, Vec<usize>)> {
+        astar(&self.graph, src_tower, |tower_node| tower_node == dst_tower,
+              |(_, _, &weight)| weight, |_| 0)
+    }
+
+    /// A shortest path in the overlay between two towers, that goes only through usable towers
+    /// (src_tower and dst_tower are always usable).
+    /// Returns (length, towers), or None if there is no such path.
+    pub fn overlay_path_filtered<F>(&self, src_tower: usize, dst_tower: usize, 
+                                    is_usable: F) -> Option<(u64, Vec<usize>)>
+        where F: Fn(usize) -> bool {
+
+        let filtered = NodeFiltered::from_fn(&self.graph, |tower_node| {
+            tower_node == src_tower || tower_node == dst_tower || is_usable(tower_node)
+        });
+        astar(&filtered, src_tower, |tower_node| tower_node == dst_tower,
+              |(_, _, &weight)| weight, |_| 0)
+    }
+}
+
 /// Check if overlay directed graph of towers is connected.
 /// Returns (connected, strongly_connected)
 pub fn is_connected(chosen_towers: &Vec<Vec<usize>>, 
         towers_info: &Vec<Vec<Option<LocalTowerInfo>>>) -> (bool, bool) {
 
-    // An overlay directed graph of the towers in the network
-    // and the connections between them.
-    // A tower T is connected to a tower T' if T' is the closest tower to T of some color.
-    let mut towers_graph: graphmap::DiGraphMap<usize,()> = 
-        graphmap::DiGraphMap::new();
+    TowersOverlay::new(chosen_towers, towers_info).is_connected()
+}
 
-    // Add towers as nodes to the graph:
-    for tower_color in 0 .. chosen_towers.len() {
-        for tower_index in 0 .. chosen_towers[tower_color].len() {
-            towers_graph.add_node(chosen_towers[tower_color][tower_index]);
-        }
+/// Check if overlay directed graph of towers is connected, using a CompactTowersInfo.
+/// Returns (connected, strongly_connected)
+pub fn is_connected_compact(chosen_towers: &Vec<Vec<usize>>, 
+        towers_info: &CompactTowersInfo) -> (bool, bool) {
+
+    TowersOverlay::new_compact(chosen_towers, towers_info).is_connected()
+}
+
+/// Check if overlay directed graph of indexers is connected.
+/// Returns (connected, strongly_connected)
+pub fn is_indexers_connected(indexers: &[usize], 
+        indexers_info: &Vec<Vec<LocalTowerInfo>>) -> (bool, bool) {
+
+    TowersOverlay::new_indexers(indexers, indexers_info).is_connected()
+}
+
+/// Make the overlay strongly connected by adding a minimum amount of links between towers.
+/// The length of every link is the distance between the two towers in the network.
+/// Returns the added links.
+pub fn repair_overlay<Node: Hash + Eq + Clone>(net: &Network<Node>, 
+        overlay: &mut TowersOverlay) -> Vec<(usize, usize)> {
+
+    let links = overlay.strong_connectivity_links();
+    for &(src_tower, dst_tower) in &links {
+        let length = net.dist(src_tower, dst_tower).unwrap();
+        overlay.add_link(src_tower, dst_tower, length);
     }
+    links
+}
 
-    // For every tower, add all connections to closest local towers as nodes.
-    let graph_nodes = towers_graph.nodes().collect::<Vec<usize>>();
-    for tower_node in graph_nodes {
-        for tower_color in 0 .. chosen_towers.len() {
-            towers_graph.add_edge(tower_node, 
-                  towers_info[tower_node][tower_color].clone().unwrap().tower_node,());
-        }
+/// A route from a source node to a destination node through the towers overlay.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TowersRoute {
+    /// Length of the path from the source up to its local tower.
+    pub climb_len: u64,
+    /// Length of the path inside the overlay, from the source's local tower to the
+    /// destination's local tower.
+    pub overlay_len: u64,
+    /// Length of the path from the destination's local tower down to the destination.
+    pub descend_len: u64,
+}
+
+impl TowersRoute {
+    pub fn total_len(&self) -> u64 {
+        self.climb_len + self.overlay_len + self.descend_len
+    }
+}
+
+/// Find a route from src_node to dst_node through the towers overlay.
+/// dst_node is addressed by its local towers. The source climbs by gateways to its local
+/// tower of some color, the packet traverses the overlay to dst_node's local tower of the
+/// same color, and descends to dst_node along the reversed gateways path of dst_node.
+/// The color with the shortest full route is picked. Colors without a local tower, or
+/// without an overlay path between the two towers, are skipped.
+/// Returns None if no color has a route.
+pub fn find_path_towers(src_node: usize, dst_node: usize, 
+        towers_info: &Vec<Vec<Option<LocalTowerInfo>>>,
+        overlay: &TowersOverlay) -> Option<TowersRoute> {
+
+    if src_node == dst_node {
+        return Some(TowersRoute {
+            climb_len: 0,
+            overlay_len: 0,
+            descend_len: 0,
+        });
     }
 
-    let sconnected_comps = kosaraju_scc(&towers_graph);
-    (connected_components(&towers_graph) == 1, sconnected_comps.len() == 1)
+    // Try every color that both nodes know a local tower of, and pick the shortest route:
+    let num_colors = towers_info[src_node].len();
+    (0 .. num_colors)
+        .filter_map(|tower_color| {
+            let src_info = towers_info[src_node][tower_color].as_ref()?;
+            let dst_info = towers_info[dst_node][tower_color].as_ref()?;
+            let route = TowersRoute {
+                climb_len: src_info.distance,
+                overlay_len: overlay.overlay_dist(src_info.tower_node, dst_info.tower_node)?,
+                descend_len: dst_info.distance,
+            };
+            Some((route.total_len(), tower_color, route))
+        })
+        .min_by_key(|&(total_len, tower_color, _)| (total_len, tower_color))
+        .map(|(_, _, route)| route)
 }
 
 
@@ -271,6 +1164,7 @@ mod tests {
     extern crate rand;
     use super::*;
     use network_gen::gen_network;
+    use signed_chain::{gen_key_pair};
     use self::rand::{StdRng};
 
     #[test]
@@ -287,4 +1181,295 @@ mod tests {
 
     }
 
+    #[test]
+    fn test_calc_towers_info_weighted() {
+        // Generate a random weighted network:
+        let seed: &[_] = &[1,2,3,4,7];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let net = gen_network(0, 6, 13, 1, 10, &mut rng);
+
+        let chosen_towers = choose_towers(&net, 3, 4, &mut rng);
+        let towers_info = calc_towers_info(&net, &chosen_towers);
+        assert!(is_towers_info_filled(&towers_info));
+        let mem_heavy_info = calc_towers_info_mem_heavy(&net, &chosen_towers);
+
+        for node in 0 .. net.igraph.node_count() {
+            for tower_color in 0 .. chosen_towers.len() {
+                // The local tower is the closest by weighted distance:
+                let ltower_info = towers_info[node][tower_color].clone().unwrap();
+                let min_dist = chosen_towers[tower_color].iter()
+                    .map(|&tower_node| net.dist(node, tower_node).unwrap())
+                    .min().unwrap();
+                assert!(ltower_info.distance == min_dist);
+                assert!(ltower_info.distance == net.dist(node, ltower_info.tower_node).unwrap());
+                // The gateway leads to the same tower:
+                if ltower_info.gateway != node {
+                    let gateway_info = towers_info[ltower_info.gateway][tower_color].clone().unwrap();
+                    let weight = *net.igraph.edge_weight(node, ltower_info.gateway).unwrap();
+                    assert!(gateway_info.tower_node == ltower_info.tower_node);
+                    assert!(gateway_info.distance + weight == ltower_info.distance);
+                }
+                // The memory heavy calculation finds the same distances:
+                assert!(mem_heavy_info[node][tower_color].clone().unwrap().distance ==
+                        ltower_info.distance);
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_remove_tower() {
+        // Generate a random weighted network:
+        let seed: &[_] = &[1,2,3,4,8];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let net = gen_network(0, 6, 13, 1, 10, &mut rng);
+
+        let mut chosen_towers = choose_towers(&net, 3, 4, &mut rng);
+        let mut towers_info = calc_towers_info(&net, &chosen_towers);
+
+        // Remove a tower. Some nodes temporarily have no local tower:
+        let tower_node = chosen_towers[1][0];
+        let stats = remove_tower(&net, &mut chosen_towers, &mut towers_info, 1, tower_node);
+        assert!(chosen_towers[1].len() == 2);
+        assert!(stats.num_rounds > 0);
+        assert!(stats.num_unfilled_rounds > 0);
+        assert!(stats.num_messages >= stats.num_rounds);
+        // Only nodes that were reached by the re-propagation know about the removal:
+        assert!(stats.num_notified > 1);
+        assert!(stats.num_notified <= net.igraph.node_count());
+        assert!(towers_info == calc_towers_info(&net, &chosen_towers));
+
+        // Elect a new tower. All nodes keep their old local tower until they hear
+        // about the new one:
+        let new_tower_node = (0 .. net.igraph.node_count())
+            .find(|node| chosen_towers[1].binary_search(node).is_err())
+            .unwrap();
+        let stats = add_tower(&net, &mut chosen_towers, &mut towers_info, 1, new_tower_node);
+        assert!(stats.num_rounds > 0);
+        assert!(stats.num_unfilled_rounds == 0);
+        assert!(stats.num_notified == 0);
+        assert!(towers_info == calc_towers_info(&net, &chosen_towers));
+
+        // Removing all towers of a color leaves all nodes without a local tower of this color:
+        for tower_node in chosen_towers[2].clone() {
+            remove_tower(&net, &mut chosen_towers, &mut towers_info, 2, tower_node);
+        }
+        assert!(towers_info.iter().all(|node_info| node_info[2].is_none()));
+        assert!(!is_towers_info_filled(&towers_info));
+    }
+
+    #[test]
+    fn test_tower_update() {
+        let seed: &[_] = &[1,2,3,4,9];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let net = gen_network(1, 6, 13, 1, 2, &mut rng);
+
+        let chosen_towers = choose_towers(&net, 2, 3, &mut rng);
+        let towers_info = calc_towers_info(&net, &chosen_towers);
+        let key_pairs = (0 .. net.igraph.node_count())
+            .map(|_| gen_key_pair(&mut rng))
+            .collect::<Vec<_>>();
+        let public_keys = key_pairs.iter().map(public_key_bytes).collect::<Vec<_>>();
+
+        for sender in 0 .. net.igraph.node_count() {
+            let receiver = net.igraph.neighbors(sender).next().unwrap();
+            let update = make_tower_update(&net, &towers_info, sender, receiver, 1, 
+                                           &key_pairs, 1000).unwrap();
+            // All weights are 1, so the chain is as long as the distance:
+            assert!(update.chain.len() as u64 == update.local_tower_info.distance);
+            assert!(verify_tower_update(&update, receiver, &public_keys, 1005, 10));
+            assert!(!verify_tower_update(&update, receiver, &public_keys, 1011, 10));
+            assert!(!verify_tower_update(&update, sender, &public_keys, 1005, 10));
+
+            // The sender can not claim another tower:
+            let mut forged = update.clone();
+            forged.local_tower_info.tower_node = (update.local_tower_info.tower_node + 1) 
+                % net.igraph.node_count();
+            assert!(!verify_tower_update(&forged, receiver, &public_keys, 1005, 10));
+
+            // The sender can not claim a distance shorter than the chain:
+            let mut forged = update.clone();
+            forged.local_tower_info.distance -= 1;
+            assert!(!verify_tower_update(&forged, receiver, &public_keys, 1005, 10));
+            forged.local_tower_info.distance = 0;
+            assert!(!verify_tower_update(&forged, receiver, &public_keys, 1005, 10));
+        }
+    }
+
+    #[test]
+    fn test_calc_indexers_info() {
+        // Generate a random weighted network:
+        let seed: &[_] = &[1,2,3,4,10];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let net = gen_network(0, 6, 13, 1, 10, &mut rng);
+
+        let k = 4;
+        let indexers = choose_indexers(&net, 12, &mut rng);
+        let indexers_info = calc_indexers_info(&net, &indexers, k);
+
+        for (node, node_info) in indexers_info.iter().enumerate() {
+            assert!(node_info.len() == k);
+            // Every node knows its k closest indexers:
+            let mut dists = indexers.iter()
+                .map(|&indexer| net.dist(node, indexer).unwrap())
+                .collect::<Vec<u64>>();
+            dists.sort();
+            for i in 0 .. k {
+                assert!(node_info[i].distance == dists[i]);
+                assert!(node_info[i].distance == 
+                        net.dist(node, node_info[i].tower_node).unwrap());
+            }
+        }
+
+        assert!(is_indexers_connected(&indexers, &indexers_info).0);
+        // Knowing all indexers gives a strongly connected overlay:
+        let indexers_info = calc_indexers_info(&net, &indexers, indexers.len());
+        assert!(is_indexers_connected(&indexers, &indexers_info) == (true, true));
+    }
+
+    #[test]
+    fn test_calc_towers_info_compact() {
+        // Generate a random weighted network:
+        let seed: &[_] = &[1,2,3,4,11];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let net = gen_network(3, 7, 15, 1, 10, &mut rng);
+
+        let chosen_towers = choose_towers(&net, 3, 8, &mut rng);
+        let compact_info = calc_towers_info_compact(&net, &chosen_towers);
+        let towers_info = calc_towers_info(&net, &chosen_towers);
+        assert!(compact_info.is_filled());
+        assert!(compact_info.to_towers_info() == towers_info);
+        assert!(is_connected_compact(&chosen_towers, &compact_info) ==
+                is_connected(&chosen_towers, &towers_info));
+        assert!(compact_info.mem_size() == 12 * net.igraph.node_count() * 8);
+
+        let empty_info = CompactTowersInfo::new(4, 2);
+        assert!(empty_info.get(3, 1).is_none());
+        assert!(!empty_info.is_filled());
+    }
+
+    #[test]
+    fn test_towers_bandwidth() {
+        let seed: &[_] = &[1,2,3,4,12];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let net = gen_network(1, 7, 15, 1, 2, &mut rng);
+
+        let chosen_towers = choose_towers(&net, 2, 6, &mut rng);
+        let (towers_info, bandwidth) = calc_towers_info_bandwidth(&net, &chosen_towers);
+        assert!(towers_info == calc_towers_info(&net, &chosen_towers));
+        assert!(bandwidth.sent_messages.len() == net.igraph.node_count());
+        // Every node announces its local tower of every color at least once,
+        // and a message may carry many entries:
+        for node in 0 .. net.igraph.node_count() {
+            let degree = net.igraph.neighbors(node).count();
+            assert!(bandwidth.sent_entries[node] >= degree * chosen_towers.len());
+            assert!(bandwidth.sent_messages[node] >= degree);
+            assert!(bandwidth.sent_messages[node] <= bandwidth.sent_entries[node]);
+        }
+        assert!(bandwidth.avg_entries() > bandwidth.avg_messages());
+
+        let (towers, dist_vectors, overlay_bandwidth) = 
+            calc_towers_dist_vectors(&chosen_towers, &towers_info);
+        assert!(towers.len() == overlay_bandwidth.sent_messages.len());
+        let overlay = TowersOverlay::new(&chosen_towers, &towers_info);
+        for i in 0 .. towers.len() {
+            for j in 0 .. towers.len() {
+                assert!(dist_vectors[i][j] == overlay.overlay_dist(towers[i], towers[j]));
+            }
+        }
+        assert!(overlay_bandwidth.avg_entries() >= overlay_bandwidth.avg_messages());
+    }
+
+    #[test]
+    fn test_strong_connectivity_links() {
+        // Overlays given as lists of links:
+        let cases: Vec<(Vec<(usize, usize)>, usize)> = vec![
+            // Already strongly connected:
+            (vec![(0, 1), (1, 0)], 0),
+            // A path:
+            (vec![(0, 1), (1, 2)], 1),
+            // Two sources and one sink:
+            (vec![(0, 2), (1, 2)], 2),
+            // One source and three sinks:
+            (vec![(0, 1), (0, 2), (0, 3)], 3),
+            // Two separate components, and an isolated tower:
+            (vec![(0, 1), (1, 0), (2, 3), (4, 4)], 3),
+            // Sources that reach only one common sink:
+            (vec![(0, 4), (1, 4), (2, 5), (3, 5), (5, 4)], 4),
+        ];
+
+        for (links, num_needed) in cases {
+            let mut overlay = TowersOverlay {
+                graph: graphmap::DiGraphMap::new(),
+            };
+            for (a, b) in links {
+                overlay.add_link(a, b, 1);
+            }
+            let extra_links = overlay.strong_connectivity_links();
+            assert!(extra_links.len() == num_needed);
+            for (a, b) in extra_links {
+                overlay.add_link(a, b, 1);
+            }
+            assert!(overlay.is_connected() == (true, true));
+        }
+    }
+
+    #[test]
+    fn test_find_path_towers() {
+        // Generate a random network:
+        let seed: &[_] = &[1,2,3,4,6];
+        let mut rng: StdRng = rand::SeedableRng::from_seed(seed);
+        let net = gen_network(0, 7, 15, 1, 2, &mut rng);
+
+        let chosen_towers = choose_towers(&net, 4, 16, &mut rng);
+        let towers_info = calc_towers_info(&net, &chosen_towers);
+        assert!(is_connected(&chosen_towers, &towers_info) == (true, true));
+        let overlay = TowersOverlay::new(&chosen_towers, &towers_info);
+
+        let node_count = net.igraph.node_count();
+        for i in 0 .. 50 {
+            let src_node = (i * 7) % node_count;
+            let dst_node = (i * 13 + 5) % node_count;
+            // The overlay is strongly connected, so routing always succeeds:
+            let route = find_path_towers(src_node, dst_node, &towers_info, &overlay).unwrap();
+            assert!(route.total_len() >= net.dist(src_node, dst_node).unwrap());
+        }
+
+        assert!(find_path_towers(3, 3, &towers_info, &overlay).unwrap().total_len() == 0);
+
+        // The overlay path agrees with the overlay distance:
+        let towers = chosen_towers.iter().flatten().cloned().collect::<Vec<usize>>();
+        for &src_tower in &towers[.. 8] {
+            for &dst_tower in &towers {
+                let (path_len, path) = overlay.overlay_path(src_tower, dst_tower).unwrap();
+                assert!(Some(path_len) == overlay.overlay_dist(src_tower, dst_tower));
+                assert!(path[0] == src_tower);
+                assert!(path[path.len() - 1] == dst_tower);
+            }
+        }
+
+        // Remove all towers of one color. Routing uses the other colors:
+        let mut chosen_towers = chosen_towers;
+        let mut towers_info = towers_info;
+        for tower_node in chosen_towers[0].clone() {
+            remove_tower(&net, &mut chosen_towers, &mut towers_info, 0, tower_node);
+        }
+        assert!(!is_towers_info_filled(&towers_info));
+        let overlay = TowersOverlay::new(&chosen_towers, &towers_info);
+        for i in 0 .. 50 {
+            let src_node = (i * 7) % node_count;
+            let dst_node = (i * 13 + 5) % node_count;
+            let route = find_path_towers(src_node, dst_node, &towers_info, &overlay).unwrap();
+            assert!(route.total_len() >= net.dist(src_node, dst_node).unwrap());
+        }
+
+        // No color is left:
+        for tower_color in 1 .. chosen_towers.len() {
+            for tower_node in chosen_towers[tower_color].clone() {
+                remove_tower(&net, &mut chosen_towers, &mut towers_info, tower_color, tower_node);
+            }
+        }
+        let overlay = TowersOverlay::new(&chosen_towers, &towers_info);
+        assert!(find_path_towers(0, 1, &towers_info, &overlay).is_none());
+    }
+
 }
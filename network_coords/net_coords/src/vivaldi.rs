@@ -0,0 +1,285 @@
+extern crate rand;
+
+use std::hash::Hash;
+
+use self::rand::{Rng};
+use self::rand::distributions::{IndependentSample, Range};
+
+use network::{Network};
+use landmarks::coord_mappers::{CoordMetric};
+
+/// A Vivaldi coordinate: A point in Euclidean space, together with a height.
+/// The height models the access link of a node, which is part of every path to the node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VivaldiCoord {
+    pub pos: Vec<f64>,
+    pub height: f64,
+}
+
+impl VivaldiCoord {
+    pub fn origin(dims: usize) -> VivaldiCoord {
+        VivaldiCoord {
+            pos: vec![0.0; dims],
+            height: 0.0,
+        }
+    }
+
+    /// Predicted distance between two coordinates.
+    pub fn dist(&self, other: &VivaldiCoord) -> f64 {
+        euclid_norm(&self.pos, &other.pos) + self.height + other.height
+    }
+}
+
+fn euclid_norm(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// The state of a node in the Vivaldi algorithm.
+#[derive(Clone, Debug)]
+pub struct VivaldiNode {
+    pub coord: VivaldiCoord,
+    /// The node's estimate of the relative error of its own coordinate.
+    pub error: f64,
+}
+
+/// Parameters of the Vivaldi algorithm.
+#[derive(Clone, Debug)]
+pub struct VivaldiParams {
+    /// Dimension of the Euclidean part of the coordinates.
+    pub dims: usize,
+    /// Fraction of the distance a node moves toward its rest position (c_c).
+    pub cc: f64,
+    /// Weight of a new sample in the moving average of the error estimate (c_e).
+    pub ce: f64,
+}
+
+/// Initialize all nodes at the origin, with the maximal error estimate.
+pub fn init_vivaldi<Node>(net: &Network<Node>, dims: usize) -> Vec<VivaldiNode> {
+    (0 .. net.igraph.node_count())
+        .map(|_| VivaldiNode {
+            coord: VivaldiCoord::origin(dims),
+            error: 1.0,
+        })
+        .collect()
+}
+
+/// Update node after measuring the distance rtt to remote.
+/// Returns the distance node's coordinate has moved.
+pub fn vivaldi_update<R: Rng>(node: &mut VivaldiNode, remote: &VivaldiNode, rtt: f64,
+                              params: &VivaldiParams, rng: &mut R) -> f64 {
+
+    assert!(rtt > 0.0, "Measured distance must be positive!");
+    let predicted = node.coord.dist(&remote.coord);
+
+    // Sample weight balances local and remote error:
+    let weight = node.error / (node.error + remote.error);
+    let sample_error = (predicted - rtt).abs() / rtt;
+    node.error = sample_error * params.ce * weight + node.error * (1.0 - params.ce * weight);
+
+    // Direction from remote to node. The heights are added, because both of them
+    // are part of the path:
+    let mut direction: Vec<f64> = node.coord.pos.iter()
+        .zip(&remote.coord.pos)
+        .map(|(x, y)| x - y)
+        .collect();
+    let mut height_direction = node.coord.height + remote.coord.height;
+    let mut norm = (direction.iter().map(|x| x * x).sum::<f64>() +
+                    height_direction * height_direction).sqrt();
+    if norm == 0.0 {
+        // Both nodes are at the same place. Pick a random direction:
+        let unit_range: Range<f64> = Range::new(-1.0, 1.0);
+        for x in &mut direction {
+            *x = unit_range.ind_sample(rng);
+        }
+        height_direction = 0.0;
+        norm = direction.iter().map(|x| x * x).sum::<f64>().sqrt();
+    }
+
+    let force = params.cc * weight * (rtt - predicted);
+    for (x, d) in node.coord.pos.iter_mut().zip(&direction) {
+        *x += force * d / norm;
+    }
+    let old_height = node.coord.height;
+    node.coord.height = (node.coord.height + force * height_direction / norm).max(0.0);
+
+    let movement = force.abs() * (direction.iter().map(|d| d * d).sum::<f64>()).sqrt() / norm;
+    movement + (node.coord.height - old_height).abs()
+}
+
+/// Statistics of one round of Vivaldi.
+#[derive(Clone, Debug)]
+pub struct VivaldiRoundStats {
+    /// Average error estimate of all nodes.
+    pub avg_error: f64,
+    /// Average distance nodes have moved during the round.
+    pub avg_movement: f64,
+}
+
+/// Choose the peers every node exchanges coordinates with: All of its neighbors, and
+/// num_remote_peers random nodes. A node learns about random nodes from its neighbors,
+/// and measures the distance to them directly.
+/// Returns, for every node, a list of (peer_index, measured distance).
+pub fn choose_peers<Node: Hash + Eq + Clone, R: Rng>(net: &Network<Node>,
+        num_remote_peers: usize, rng: &mut R) -> Vec<Vec<(usize, u64)>> {
+
+    let num_nodes = net.igraph.node_count();
+    let node_range: Range<usize> = Range::new(0, num_nodes);
+    (0 .. num_nodes)
+        .map(|node_index| {
+            let mut peers = net.igraph.edges(node_index)
+                .map(|(_, nei_index, &weight)| (nei_index, weight))
+                .collect::<Vec<(usize, u64)>>();
+            // Sort for determinism:
+            peers.sort();
+            for _ in 0 .. num_remote_peers {
+                let peer_index = node_range.ind_sample(rng);
+                if peer_index == node_index || peers.iter().any(|&(i, _)| i == peer_index) {
+                    continue;
+                }
+                peers.push((peer_index, net.dist(node_index, peer_index).unwrap()));
+            }
+            peers
+        })
+        .collect()
+}
+
+/// Perform one round of Vivaldi: Every node measures its distance to a random peer,
+/// and updates its coordinate.
+pub fn vivaldi_round<R: Rng>(peers: &[Vec<(usize, u64)>], nodes: &mut [VivaldiNode],
+                             params: &VivaldiParams, rng: &mut R) -> VivaldiRoundStats {
+
+    let num_nodes = nodes.len();
+    let mut sum_movement = 0.0;
+    for node_index in 0 .. num_nodes {
+        if peers[node_index].is_empty() {
+            continue;
+        }
+        let peer_range: Range<usize> = Range::new(0, peers[node_index].len());
+        let (peer_index, dist) = peers[node_index][peer_range.ind_sample(rng)];
+        let remote = nodes[peer_index].clone();
+        sum_movement += vivaldi_update(&mut nodes[node_index], &remote, dist as f64,
+                                       params, rng);
+    }
+
+    VivaldiRoundStats {
+        avg_error: nodes.iter().map(|node| node.error).sum::<f64>() / (num_nodes as f64),
+        avg_movement: sum_movement / (num_nodes as f64),
+    }
+}
+
+/// Run num_rounds rounds of Vivaldi. Returns the statistics of every round.
+pub fn converge_vivaldi<R: Rng>(peers: &[Vec<(usize, u64)>], nodes: &mut [VivaldiNode],
+        params: &VivaldiParams, num_rounds: usize, rng: &mut R) -> Vec<VivaldiRoundStats> {
+
+    (0 .. num_rounds)
+        .map(|_| vivaldi_round(peers, nodes, params, rng))
+        .collect()
+}
+
+/// Vivaldi coordinates of all nodes predict distances between nodes.
+impl CoordMetric for [VivaldiNode] {
+    fn approx_dist(&self, u: usize, v: usize) -> f64 {
+        self[u].coord.dist(&self[v].coord)
+    }
+}
+
+/// Relative error of the predicted distance for every pair of nodes in node_pairs.
+pub fn vivaldi_rel_errors<Node: Hash + Eq + Clone>(net: &Network<Node>, nodes: &[VivaldiNode],
+        node_pairs: &[(usize, usize)]) -> Vec<f64> {
+
+    node_pairs.iter()
+        .map(|&(u, v)| {
+            let dist = net.dist(u, v).unwrap() as f64;
+            (nodes[u].coord.dist(&nodes[v].coord) - dist).abs() / dist
+        })
+        .collect()
+}
+
+/// Route greedily from src_node to dst_node. Every node knows the amount_close closest nodes
+/// around it, and moves to the known node with the smallest node_dist to dst_node.
+/// Returns None if routing got stuck at a node with no known node closer to dst_node.
+/// Otherwise returns the path length, in hops.
+pub fn find_path_greedy<Node: Hash + Eq + Clone, F>(src_node: usize, dst_node: usize,
+        amount_close: usize, net: &Network<Node>, node_dist: F) -> Option<u64>
+    where F: Fn(usize, usize) -> f64 {
+
+    let mut total_distance: u64 = 0;
+    let mut cur_node = src_node;
+    while cur_node != dst_node {
+        let (new_node, new_dist, _) = net.closest_nodes_structure(cur_node)
+            .take(amount_close)
+            .min_by(|&(i, _, _), &(j, _, _)|
+                    node_dist(i, dst_node).partial_cmp(&node_dist(j, dst_node)).unwrap()
+                    .then(i.cmp(&j)))?;
+
+        if node_dist(new_node, dst_node) >= node_dist(cur_node, dst_node) && new_node != dst_node {
+            return None;
+        }
+        total_distance += new_dist;
+        cur_node = new_node;
+    }
+    Some(total_distance)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use self::rand::{StdRng, SeedableRng};
+    use network::{grid2_net};
+
+    #[test]
+    fn test_vivaldi_coord_dist() {
+        let a = VivaldiCoord {
+            pos: vec![0.0, 0.0],
+            height: 1.0,
+        };
+        let b = VivaldiCoord {
+            pos: vec![3.0, 4.0],
+            height: 2.0,
+        };
+        assert!((a.dist(&b) - 8.0).abs() < 1e-9);
+        assert!((a.dist(&a) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_vivaldi_grid() {
+        let seed: &[_] = &[1,2,3,4,13];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let k = 6;
+        let net = grid2_net(k);
+        let params = VivaldiParams {
+            dims: 2,
+            cc: 0.25,
+            ce: 0.25,
+        };
+        let mut nodes = init_vivaldi(&net, params.dims);
+        let peers = choose_peers(&net, 4, &mut rng);
+        let stats = converge_vivaldi(&peers, &mut nodes, &params, 300, &mut rng);
+        assert!(stats.len() == 300);
+        assert!(stats[299].avg_error < stats[0].avg_error);
+
+        // A grid embeds well in the plane:
+        let node_pairs = (0 .. k * k)
+            .map(|i| (i, (i * 7 + 11) % (k * k)))
+            .filter(|&(u, v)| u != v)
+            .collect::<Vec<(usize, usize)>>();
+        let mut rel_errors = vivaldi_rel_errors(&net, &nodes, &node_pairs);
+        rel_errors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert!(rel_errors[rel_errors.len() / 2] < 0.3);
+
+        // Greedy routing with the exact distance always succeeds:
+        let exact_dist = |u: usize, v: usize| net.dist(u, v).unwrap() as f64;
+        assert!(find_path_greedy(0, k * k - 1, 4, &net, exact_dist) == Some(2 * (k as u64 - 1)));
+
+        let num_found = node_pairs.iter()
+            .filter(|&&(u, v)| find_path_greedy(u, v, 8, &net,
+                        |x, y| nodes[x].coord.dist(&nodes[y].coord)).is_some())
+            .count();
+        assert!(num_found * 2 > node_pairs.len());
+    }
+}